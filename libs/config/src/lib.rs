@@ -1,6 +1,38 @@
-use std::{collections::btree_map::Range, f32::consts::*, ops::RangeInclusive};
+use std::{f32::consts::*, ops::RangeInclusive};
 use rand::{distributions::uniform::SampleRange, Rng, RngCore};
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MutationKind {
+    // Normally distributed, mutation_coef is the standard deviation
+    Gaussian,
+    // Deb's polynomial mutation, mutation_coef scales the spread
+    Polynomial { eta: f32 },
+    // Each gene carries its own step size, mutation_coef is the starting value
+    SelfAdaptive,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeStrategy {
+    // Mutant built around a random individual: x_r1 + F * (x_r2 - x_r3)
+    Rand1Bin,
+    // Mutant built around the fittest individual: x_best + F * (x_r1 - x_r2)
+    Best1Bin,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DeControl {
+    // Same differential weight `f` and crossover rate `cr` for everyone
+    Fixed { f: f32, cr: f32 },
+    // jDE: each individual carries its own F and CR, which evolve with it
+    SelfAdaptive,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Engine {
+    Genetic,
+    DifferentialEvolution { strategy: DeStrategy, control: DeControl },
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct Config {
     pub num_eye_cells: usize,
@@ -10,6 +42,8 @@ pub struct Config {
     pub fov_angle:f32,
     pub mutation_chance: f32,
     pub mutation_coef: f32,
+    pub mutation_kind: MutationKind,
+    pub engine: Engine,
 }
 
 impl Config {
@@ -29,6 +63,8 @@ impl Config {
             fov_angle: PI + FRAC_PI_4, // Don't allow modification for angle as optimal fov angle is always 360
             mutation_chance,
             mutation_coef,
+            mutation_kind: MutationKind::Gaussian,
+            engine: Engine::Genetic,
         }
     }
 
@@ -55,6 +91,8 @@ impl Config {
             fov_angle: PI + FRAC_PI_4,
             mutation_chance,
             mutation_coef,
+            mutation_kind: MutationKind::Gaussian,
+            engine: Engine::Genetic,
         }
     }
 
@@ -82,6 +120,8 @@ impl Config {
             fov_angle: PI + FRAC_PI_4,
             mutation_chance,
             mutation_coef,
+            mutation_kind: MutationKind::Gaussian,
+            engine: Engine::Genetic,
         }
     }
 
@@ -101,6 +141,8 @@ impl Config {
             fov_angle: PI + FRAC_PI_4,
             mutation_chance,
             mutation_coef,
+            mutation_kind: MutationKind::Gaussian,
+            engine: Engine::Genetic,
         }
     }
 }
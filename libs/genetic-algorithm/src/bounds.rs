@@ -0,0 +1,161 @@
+use crate::*;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepairMethod {
+    // Snap to the nearest bound
+    Clamp,
+    // Bounce back off the bound by however far it overshot
+    Reflect,
+    // Re-enter from the opposite bound, as if the range were a circle
+    Wrap,
+}
+
+impl RepairMethod {
+    pub fn repair(self, value: f32, bounds: &RangeInclusive<f32>) -> f32 {
+        let (min, max) = (*bounds.start(), *bounds.end());
+        if bounds.contains(&value) {
+            return value;
+        }
+
+        let width = max - min;
+        if width <= 0.0 || !value.is_finite() {
+            return value.clamp(min, max);
+        }
+
+        match self {
+            Self::Clamp => value.clamp(min, max),
+            Self::Reflect => {
+                let offset = (value - min).rem_euclid(2.0 * width);
+                min + if offset > width {2.0 * width - offset} else {offset}
+            }
+            Self::Wrap => min + (value - min).rem_euclid(width),
+        }
+    }
+}
+
+/// Pulls genes back within their bounds after crossover and mutation. Uses
+/// each chromosome's own bounds, falling back to `default_bounds` for
+/// chromosomes without any.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundsRepair {
+    method: RepairMethod,
+    default_bounds: Option<RangeInclusive<f32>>,
+}
+
+impl BoundsRepair {
+    pub fn new(method: RepairMethod) -> Self {
+        Self {
+            method,
+            default_bounds: None,
+        }
+    }
+
+    pub fn with_default_bounds(mut self, bounds: RangeInclusive<f32>) -> Self {
+        assert!(bounds.start() <= bounds.end());
+        self.default_bounds = Some(bounds);
+        self
+    }
+
+    pub fn apply<G: Gene>(&self, chromosome: &mut Chromosome<G>) {
+        let Some(bounds) = self.bounds_of(chromosome) else {
+            return;
+        };
+
+        for (gene, bounds) in chromosome.iter_mut().zip(&bounds) {
+            let value = gene.value();
+            if !bounds.contains(&value) {
+                *gene = G::from_value(self.method.repair(value, bounds));
+            }
+        }
+    }
+
+    // Like `violation`, but measured against the bounds `apply` would
+    // enforce, so chromosomes without bounds count against `default_bounds`
+    pub fn violation<G: Gene>(&self, chromosome: &Chromosome<G>) -> f32 {
+        match self.bounds_of(chromosome) {
+            Some(bounds) => distance_outside(chromosome, &bounds),
+            None => 0.0,
+        }
+    }
+
+    fn bounds_of<G>(&self, chromosome: &Chromosome<G>) -> Option<Vec<RangeInclusive<f32>>> {
+        match (chromosome.bounds(), &self.default_bounds) {
+            (Some(bounds), _) => Some(bounds.to_vec()),
+            (None, Some(bounds)) => Some(vec![bounds.clone(); chromosome.len()]),
+            (None, None) => None,
+        }
+    }
+}
+
+// Total distance by which genes lie outside their bounds, a handy base for
+// `GeneticAlgorithm::with_penalty`
+pub fn violation<G: Gene>(chromosome: &Chromosome<G>) -> f32 {
+    match chromosome.bounds() {
+        Some(bounds) => distance_outside(chromosome, bounds),
+        None => 0.0,
+    }
+}
+
+fn distance_outside<G: Gene>(chromosome: &Chromosome<G>, bounds: &[RangeInclusive<f32>]) -> f32 {
+    chromosome
+        .iter()
+        .zip(bounds)
+        .map(|(gene, bounds)| {
+            let value = gene.value();
+            (bounds.start() - value).max(0.0) + (value - bounds.end()).max(0.0)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::sync::Arc;
+
+    #[test]
+    fn repair_methods() {
+        let bounds = -1.0..=1.0;
+
+        assert_eq!(RepairMethod::Clamp.repair(1.5, &bounds), 1.0);
+        assert_relative_eq!(RepairMethod::Reflect.repair(1.5, &bounds), 0.5);
+        assert_relative_eq!(RepairMethod::Reflect.repair(-3.5, &bounds), 0.5);
+        assert_relative_eq!(RepairMethod::Wrap.repair(1.5, &bounds), -0.5);
+        assert_relative_eq!(RepairMethod::Wrap.repair(-1.25, &bounds), 0.75);
+
+        for method in [RepairMethod::Clamp, RepairMethod::Reflect, RepairMethod::Wrap] {
+            assert_eq!(method.repair(0.3, &bounds), 0.3);
+            assert!(bounds.contains(&method.repair(123.4, &bounds)));
+        }
+    }
+
+    #[test]
+    fn per_gene_bounds_win_over_default() {
+        let mut chromosome: Chromosome = [5.0, -5.0, 0.5].into_iter().collect();
+        let repair = BoundsRepair::new(RepairMethod::Clamp).with_default_bounds(-1.0..=1.0);
+
+        let mut unbounded = chromosome.clone();
+        assert_eq!(violation(&unbounded), 0.0);
+        assert_eq!(repair.violation(&unbounded), 8.0);
+        repair.apply(&mut unbounded);
+        assert_eq!(unbounded.as_slice(), &[1.0, -1.0, 0.5]);
+
+        chromosome.set_bounds(Arc::from(vec![0.0..=10.0, -2.0..=2.0, 0.0..=0.25]));
+        assert_eq!(violation(&chromosome), 3.25);
+
+        repair.apply(&mut chromosome);
+        assert_eq!(chromosome.as_slice(), &[5.0, -2.0, 0.25]);
+        assert_eq!(violation(&chromosome), 0.0);
+    }
+
+    #[test]
+    fn integer_genes_stay_integral() {
+        let mut chromosome: Chromosome<i32> = [12, -3].into_iter().collect();
+        BoundsRepair::new(RepairMethod::Reflect)
+            .with_default_bounds(0.0..=10.0)
+            .apply(&mut chromosome);
+
+        assert_eq!(chromosome.as_slice(), &[8, 3]);
+    }
+}
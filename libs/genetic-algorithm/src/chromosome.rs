@@ -1,44 +1,214 @@
-use std::ops::Index;
+use std::ops::{Index, RangeInclusive};
+use std::sync::Arc;
+use crate::gene_layout::GeneLayout;
 
 
-#[derive(Clone, Debug)]
-pub struct Chromosome {
-    genes: Vec<f32>,
+// Genes default to `f32` weights; use `bool`, integers or `usize`
+// permutations for non-neural problems
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chromosome<G = f32> {
+    genes: Vec<G>,
+    // Per-gene mutation strengths for self-adaptive mutation, empty otherwise
+    step_sizes: Vec<f32>,
+    // Which layer, neuron and input each gene belongs to. Children share
+    // their parents' copy, but separately built chromosomes each get their own
+    layout: Option<Arc<GeneLayout>>,
+    // Allowed range of every gene, enforced by `BoundsRepair`
+    bounds: Option<Arc<[RangeInclusive<f32>]>>,
 }
 
-impl Chromosome {
+impl<G> Chromosome<G> {
     pub fn len(&self) -> usize {
         self.genes.len()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &f32> {
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &G> {
         self.genes.iter()
     }
 
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f32> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut G> {
         self.genes.iter_mut()
     }
+
+    pub fn as_slice(&self) -> &[G] {
+        &self.genes
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [G] {
+        &mut self.genes
+    }
+
+    pub fn step_sizes(&self) -> &[f32] {
+        &self.step_sizes
+    }
+
+    pub fn set_step_sizes(&mut self, step_sizes: Vec<f32>) {
+        assert!(step_sizes.is_empty() || step_sizes.len() == self.len());
+        self.step_sizes = step_sizes;
+    }
+
+    pub fn layout(&self) -> Option<&GeneLayout> {
+        self.layout.as_deref()
+    }
+
+    pub fn set_layout(&mut self, layout: Arc<GeneLayout>) {
+        assert_eq!(layout.len(), self.len());
+        self.layout = Some(layout);
+    }
+
+    pub fn bounds(&self) -> Option<&[RangeInclusive<f32>]> {
+        self.bounds.as_deref()
+    }
+
+    pub fn set_bounds(&mut self, bounds: Arc<[RangeInclusive<f32>]>) {
+        assert_eq!(bounds.len(), self.len());
+        self.bounds = Some(bounds);
+    }
+
+    // Crossover only produces genes, so fill in whatever else the parents
+    // carried: a matching layout and bounds, and step sizes averaged
+    // between them
+    pub fn inherit_from(&mut self, parent_a: &Chromosome<G>, parent_b: &Chromosome<G>) {
+        if self.layout.is_none() {
+            self.layout = [parent_a, parent_b]
+                .into_iter()
+                .filter_map(|parent| parent.layout.as_ref())
+                .find(|layout| layout.len() == self.len())
+                .cloned();
+        }
+
+        if self.bounds.is_none() {
+            self.bounds = [parent_a, parent_b]
+                .into_iter()
+                .filter_map(|parent| parent.bounds.as_ref())
+                .find(|bounds| bounds.len() == self.len())
+                .cloned();
+        }
+
+        if !self.step_sizes.is_empty() {
+            return;
+        }
+
+        let usable = |parent: &Chromosome<G>| parent.step_sizes.len() == self.len() && !parent.step_sizes.is_empty();
+
+        self.step_sizes = match (usable(parent_a), usable(parent_b)) {
+            (true, true) => parent_a.step_sizes
+                .iter()
+                .zip(&parent_b.step_sizes)
+                .map(|(a, b)| 0.5 * (a + b))
+                .collect(),
+            (true, false) => parent_a.step_sizes.clone(),
+            (false, true) => parent_b.step_sizes.clone(),
+            (false, false) => Vec::new(),
+        };
+    }
+}
+
+/// Genes that can be read as a number, for distances and diversity
+/// statistics, and rebuilt from one when repairing out-of-bounds genes.
+pub trait Gene: Clone + PartialEq {
+    fn value(&self) -> f32;
+    fn from_value(value: f32) -> Self;
+}
+
+macro_rules! impl_float_gene {
+    ($($ty:ty),*) => {
+        $(
+            impl Gene for $ty {
+                fn value(&self) -> f32 {
+                    *self as f32
+                }
+
+                fn from_value(value: f32) -> Self {
+                    value as $ty
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_integer_gene {
+    ($($ty:ty),*) => {
+        $(
+            impl Gene for $ty {
+                fn value(&self) -> f32 {
+                    *self as f32
+                }
+
+                fn from_value(value: f32) -> Self {
+                    value.round() as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_float_gene!(f32, f64);
+impl_integer_gene!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl Gene for bool {
+    fn value(&self) -> f32 {
+        if *self {1.0} else {0.0}
+    }
+
+    fn from_value(value: f32) -> Self {
+        value >= 0.5
+    }
+}
+
+impl<G: Gene> Chromosome<G> {
+    // Root-mean-square gene difference, so thresholds don't depend on length
+    // Chromosomes with different layouts are compared on the genes their
+    // layouts have in common, even when their lengths happen to match
+    pub fn distance(&self, other: &Chromosome<G>) -> f32 {
+        let pairs: Vec<(f32, f32)> = match (self.layout(), other.layout()) {
+            (Some(layout), Some(other_layout)) if layout != other_layout => layout
+                .align(other_layout)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, matching)| Some((self[index].value(), other[matching?].value())))
+                .collect(),
+            _ => {
+                assert_eq!(self.len(), other.len(), "Chromosomes of different lengths need gene layouts to be compared");
+                self.iter().zip(other.iter()).map(|(a, b)| (a.value(), b.value())).collect()
+            }
+        };
+
+        if pairs.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f32 = pairs.iter().map(|(a, b)| (a - b).powi(2)).sum();
+        (sum / pairs.len() as f32).sqrt()
+    }
 }
 
-impl Index<usize> for Chromosome {
-    type Output = f32;
+impl<G> Index<usize> for Chromosome<G> {
+    type Output = G;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.genes[index]
     }
 }
 
-impl FromIterator<f32> for Chromosome {
-    fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
+impl<G> FromIterator<G> for Chromosome<G> {
+    fn from_iter<T: IntoIterator<Item = G>>(iter: T) -> Self {
         Self {
             genes: iter.into_iter().collect(),
+            step_sizes: Vec::new(),
+            layout: None,
+            bounds: None,
         }
     }
 }
 
-impl IntoIterator for Chromosome {
-    type Item = f32;
-    type IntoIter = std::vec::IntoIter<f32>;
+impl<G> IntoIterator for Chromosome<G> {
+    type Item = G;
+    type IntoIter = std::vec::IntoIter<G>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.genes.into_iter()
@@ -0,0 +1,270 @@
+use crate::*;
+use nalgebra::{DMatrix, DVector};
+use rand_distr::StandardNormal;
+
+/// Covariance matrix adaptation evolution strategy, after Hansen's "The CMA
+/// Evolution Strategy: A Tutorial". Maximizes `Individual::fitness`.
+///
+/// Used ask-and-tell style: `ask` samples chromosomes to evaluate, `tell`
+/// updates the search distribution from the evaluated individuals. `evolve`
+/// does both, mirroring `GeneticAlgorithm::evolve`.
+#[derive(Clone, Debug)]
+pub struct CmaEs {
+    // Sampled chromosomes take their layout and step sizes from here
+    template: Chromosome,
+    lambda: usize,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    chi_n: f64,
+    mean: DVector<f64>,
+    sigma: f64,
+    p_sigma: DVector<f64>,
+    p_c: DVector<f64>,
+    covariance: DMatrix<f64>,
+    // Eigendecomposition of the covariance: C = B * diag(D^2) * B^T
+    basis: DMatrix<f64>,
+    scales: DVector<f64>,
+    inv_sqrt_covariance: DMatrix<f64>,
+    // Generation the eigendecomposition was last refreshed at
+    eigen_generation: usize,
+    generation: usize,
+}
+
+impl CmaEs {
+    // Starts the search around `mean` with step size `sigma`, using the
+    // default population size for its dimension
+    pub fn new(mean: &Chromosome, sigma: f32) -> Self {
+        let n = mean.len() as f64;
+        Self::with_population_size(mean, sigma, 4 + (3.0 * n.ln()).floor() as usize)
+    }
+
+    pub fn with_population_size(mean: &Chromosome, sigma: f32, lambda: usize) -> Self {
+        assert!(!mean.is_empty());
+        assert!(sigma > 0.0);
+        assert!(lambda >= 2);
+
+        let n = mean.len() as f64;
+        let mu = lambda / 2;
+
+        let weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let weights: Vec<f64> = weights.iter().map(|w| w / total).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff));
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        let dim = mean.len();
+
+        Self {
+            template: mean.clone(),
+            lambda,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n,
+            mean: DVector::from_iterator(dim, mean.iter().map(|&gene| gene as f64)),
+            sigma: sigma as f64,
+            p_sigma: DVector::zeros(dim),
+            p_c: DVector::zeros(dim),
+            covariance: DMatrix::identity(dim, dim),
+            basis: DMatrix::identity(dim, dim),
+            scales: DVector::from_element(dim, 1.0),
+            inv_sqrt_covariance: DMatrix::identity(dim, dim),
+            eigen_generation: 0,
+            generation: 0,
+        }
+    }
+
+    pub fn population_size(&self) -> usize {
+        self.lambda
+    }
+
+    pub fn sigma(&self) -> f32 {
+        self.sigma as f32
+    }
+
+    pub fn mean(&self) -> Chromosome {
+        self.to_chromosome(&self.mean)
+    }
+
+    // Samples `population_size()` chromosomes from the current distribution
+    pub fn ask(&self, rng: &mut dyn RngCore) -> Vec<Chromosome> {
+        let dim = self.mean.len();
+
+        (0..self.lambda)
+            .map(|_| {
+                let z = DVector::from_iterator(dim, (0..dim).map(|_| rng.sample::<f64, _>(StandardNormal)));
+                let y = &self.basis * z.component_mul(&self.scales);
+                self.to_chromosome(&(&self.mean + self.sigma * y))
+            })
+            .collect()
+    }
+
+    // Moves the distribution towards the fittest individuals. They need not
+    // come from `ask`, but must have the same number of genes.
+    pub fn tell<I: Individual>(&mut self, population: &[I]) {
+        let mu = self.weights.len();
+        assert!(population.len() >= mu);
+
+        let dim = self.mean.len();
+        let n = dim as f64;
+
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+        let steps: Vec<DVector<f64>> = best_first(&fitness)
+            .into_iter()
+            .take(mu)
+            .map(|index| {
+                let chromosome = population[index].chromosome();
+                assert_eq!(chromosome.len(), dim);
+                let x = DVector::from_iterator(dim, chromosome.iter().map(|&gene| gene as f64));
+                (x - &self.mean) / self.sigma
+            })
+            .collect();
+
+        let y_w = steps
+            .iter()
+            .zip(&self.weights)
+            .fold(DVector::zeros(dim), |sum, (y, w)| sum + y * *w);
+
+        self.mean += self.sigma * &y_w;
+        self.generation += 1;
+
+        self.p_sigma = (1.0 - self.c_sigma) * &self.p_sigma
+            + (self.c_sigma * (2.0 - self.c_sigma) * self.mu_eff).sqrt() * (&self.inv_sqrt_covariance * &y_w);
+
+        let p_sigma_norm = self.p_sigma.norm();
+        let decay = (1.0 - (1.0 - self.c_sigma).powi(2 * self.generation as i32)).sqrt();
+        let h_sigma = if p_sigma_norm / decay < (1.4 + 2.0 / (n + 1.0)) * self.chi_n {1.0} else {0.0};
+
+        self.p_c = (1.0 - self.c_c) * &self.p_c
+            + h_sigma * (self.c_c * (2.0 - self.c_c) * self.mu_eff).sqrt() * &y_w;
+
+        let rank_one = &self.p_c * self.p_c.transpose();
+        let rank_mu = steps
+            .iter()
+            .zip(&self.weights)
+            .fold(DMatrix::zeros(dim, dim), |sum, (y, w)| sum + *w * (y * y.transpose()));
+        let lost_variance = (1.0 - h_sigma) * self.c_c * (2.0 - self.c_c);
+
+        self.covariance = (1.0 - self.c_1 - self.c_mu) * &self.covariance
+            + self.c_1 * (rank_one + lost_variance * &self.covariance)
+            + self.c_mu * rank_mu;
+
+        self.sigma *= ((self.c_sigma / self.d_sigma) * (p_sigma_norm / self.chi_n - 1.0)).exp();
+
+        // Decomposing is O(n^3), so only refresh once the covariance has
+        // drifted noticeably
+        let interval = self.lambda as f64 / ((self.c_1 + self.c_mu) * n * 10.0);
+        if (self.generation - self.eigen_generation) as f64 > interval {
+            self.decompose();
+        }
+    }
+
+    pub fn evolve<I: Individual>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I> {
+        self.tell(population);
+        self.ask(rng).into_iter().map(I::create).collect()
+    }
+
+    fn decompose(&mut self) {
+        self.eigen_generation = self.generation;
+
+        // Keep it exactly symmetric, rounding errors accumulate otherwise
+        let covariance = (&self.covariance + self.covariance.transpose()) * 0.5;
+        let eigen = covariance.clone().symmetric_eigen();
+
+        self.covariance = covariance;
+        self.scales = eigen.eigenvalues.map(|value| value.max(1e-20).sqrt());
+        self.basis = eigen.eigenvectors;
+
+        let inv_scales = DMatrix::from_diagonal(&self.scales.map(|scale| 1.0 / scale));
+        self.inv_sqrt_covariance = &self.basis * inv_scales * self.basis.transpose();
+    }
+
+    fn to_chromosome(&self, genes: &DVector<f64>) -> Chromosome {
+        let mut chromosome: Chromosome = genes.iter().map(|&gene| gene as f32).collect();
+        chromosome.inherit_from(&self.template, &self.template);
+        chromosome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // Negated so that maximizing fitness minimizes the function
+    fn evaluate(chromosomes: Vec<Chromosome>, function: impl Fn(&[f32]) -> f32) -> Vec<TestIndividual> {
+        chromosomes
+            .into_iter()
+            .map(|chromosome| TestIndividual::with_chromosome(-function(chromosome.as_slice()), chromosome))
+            .collect()
+    }
+
+    fn sphere(x: &[f32]) -> f32 {
+        x.iter().map(|xi| xi * xi).sum()
+    }
+
+    // Elongated valley that needs a well-adapted covariance
+    fn ellipsoid(x: &[f32]) -> f32 {
+        x.iter()
+            .enumerate()
+            .map(|(i, xi)| 1000f32.powf(i as f32 / (x.len() - 1) as f32) * xi * xi)
+            .sum()
+    }
+
+    fn minimize(function: fn(&[f32]) -> f32, generations: usize) -> f32 {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let start: Chromosome = vec![1.0; 10].into_iter().collect();
+        let mut cma_es = CmaEs::new(&start, 0.5);
+
+        let mut population = evaluate(cma_es.ask(&mut rng), function);
+        for _ in 0..generations {
+            population = evaluate(
+                cma_es.evolve(&mut rng, &population).into_iter().map(|individual| individual.chromosome().clone()).collect(),
+                function,
+            );
+        }
+
+        function(cma_es.mean().as_slice())
+    }
+
+    #[test]
+    fn converges_on_sphere() {
+        assert!(minimize(sphere, 200) < 1e-6);
+    }
+
+    #[test]
+    fn converges_on_ellipsoid() {
+        assert!(minimize(ellipsoid, 400) < 1e-4);
+    }
+
+    #[test]
+    fn samples_keep_template_layout() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut start: Chromosome = vec![0.0; 4].into_iter().collect();
+        start.set_step_sizes(vec![0.1; 4]);
+
+        let cma_es = CmaEs::with_population_size(&start, 1.0, 6);
+        let samples = cma_es.ask(&mut rng);
+
+        assert_eq!(samples.len(), 6);
+        assert!(samples.iter().all(|sample| sample.step_sizes() == start.step_sizes()));
+    }
+}
@@ -1,17 +1,178 @@
 use crate::*;
+use crate::gene_layout::Granularity;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
-pub trait CrossoverMethod {
+// Send + Sync so `evolve_parallel` can share one across threads
+pub trait CrossoverMethod<G = f32>: Send + Sync {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G>;
+}
+
+#[derive(Debug)]
+pub struct UniformCrossover;
+
+impl<G: Clone> CrossoverMethod<G> for UniformCrossover {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(a, b)| if rng.gen_bool(0.5) {a.clone()} else {b.clone()})
+            .collect()
+    }
+}
+
+/// Cuts both parents at `points` random positions and alternates between
+/// them, so runs of neighbouring genes are inherited together.
+#[derive(Debug)]
+pub struct KPointCrossover {
+    points: usize,
+}
+
+impl KPointCrossover {
+    pub fn new(points: usize) -> Self {
+        assert!(points > 0);
+        Self {
+            points,
+        }
+    }
+}
+
+impl<G: Clone> CrossoverMethod<G> for KPointCrossover {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let len = parent_a.len();
+        if len < 2 {
+            return parent_a.clone();
+        }
+
+        let mut cuts = rand::seq::index::sample(rng, len - 1, self.points.min(len - 1))
+            .into_iter()
+            .map(|cut| cut + 1)
+            .collect::<Vec<_>>();
+        cuts.sort_unstable();
+
+        let mut cuts = cuts.into_iter().peekable();
+        let mut from_a = rng.gen_bool(0.5);
+
+        (0..len)
+            .map(|i| {
+                if cuts.next_if_eq(&i).is_some() {
+                    from_a = !from_a;
+                }
+                if from_a {parent_a[i].clone()} else {parent_b[i].clone()}
+            })
+            .collect()
+    }
+}
+
+/// Weighted average of both parents: `alpha * a + (1 - alpha) * b`.
+#[derive(Debug)]
+pub struct ArithmeticCrossover {
+    // Drawn fresh for each child when `None`
+    alpha: Option<f32>,
+}
+
+impl ArithmeticCrossover {
+    pub fn new(alpha: f32) -> Self {
+        assert!((0.0..=1.0).contains(&alpha));
+        Self {
+            alpha: Some(alpha),
+        }
+    }
+
+    pub fn random() -> Self {
+        Self {
+            alpha: None,
+        }
+    }
+}
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let alpha = self.alpha.unwrap_or_else(|| rng.gen());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| alpha * a + (1.0 - alpha) * b)
+            .collect()
+    }
+}
+
+/// BLX-alpha: each gene is drawn uniformly from the parents' interval,
+/// widened by `alpha` times its length on both sides.
+#[derive(Debug)]
+pub struct BlendCrossover {
+    alpha: f32,
+}
+
+impl BlendCrossover {
+    pub fn new(alpha: f32) -> Self {
+        assert!(alpha >= 0.0);
+        Self {
+            alpha,
+        }
+    }
+}
+
+impl CrossoverMethod for BlendCrossover {
     fn crossover(&self,
         rng: &mut dyn RngCore,
         parent_a: &Chromosome,
         parent_b: &Chromosome
-    ) -> Chromosome;
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let (min, max) = if a < b {(a, b)} else {(b, a)};
+                let margin = self.alpha * (max - min);
+                min - margin + rng.gen::<f32>() * (max - min + 2.0 * margin)
+            })
+            .collect()
+    }
 }
 
+/// SBX: mimics the spread of single-point crossover on binary strings.
+/// Higher `eta` keeps children closer to their parents.
 #[derive(Debug)]
-pub struct UniformCrossover;
+pub struct SimulatedBinaryCrossover {
+    eta: f32,
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(eta: f32) -> Self {
+        assert!(eta >= 0.0);
+        Self {
+            eta,
+        }
+    }
+}
 
-impl CrossoverMethod for UniformCrossover {
+impl CrossoverMethod for SimulatedBinaryCrossover {
     fn crossover(&self,
         rng: &mut dyn RngCore,
         parent_a: &Chromosome,
@@ -22,7 +183,449 @@ impl CrossoverMethod for UniformCrossover {
         parent_a
             .iter()
             .zip(parent_b.iter())
-            .map(|(&a, &b)| if rng.gen_bool(0.5) {a} else {b})
+            .map(|(&a, &b)| {
+                let u: f32 = rng.gen();
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(1.0 / (self.eta + 1.0))
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (self.eta + 1.0))
+                };
+
+                // Pick one of the two symmetric SBX children
+                let sign = if rng.gen_bool(0.5) {1.0} else {-1.0};
+                0.5 * ((a + b) + sign * beta * (a - b))
+            })
+            .collect()
+    }
+}
+
+/// Swaps whole neurons or layers between parents, using the `GeneLayout`
+/// attached to the chromosomes, so evolved feature detectors stay intact.
+/// Parents without layouts fall back to `UniformCrossover`.
+#[derive(Debug)]
+pub struct StructuredCrossover {
+    granularity: Granularity,
+}
+
+impl StructuredCrossover {
+    pub fn new(granularity: Granularity) -> Self {
+        Self {
+            granularity,
+        }
+    }
+}
+
+impl<G: Clone> CrossoverMethod<G> for StructuredCrossover {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        // Equal lengths don't make equal topologies, so only a layout both
+        // parents share, or the only one there is, describes both
+        let layout = match (parent_a.layout(), parent_b.layout()) {
+            (Some(layout_a), Some(layout_b)) if layout_a != layout_b => {
+                return AlignedCrossover.crossover(rng, parent_a, parent_b);
+            }
+            (None, None) => return UniformCrossover.crossover(rng, parent_a, parent_b),
+            (layout_a, layout_b) => layout_a.or(layout_b).unwrap(),
+        };
+        assert_eq!(parent_a.len(), parent_b.len());
+        let mut genes: Vec<G> = parent_a.as_slice().to_vec();
+
+        for group in layout.groups(self.granularity) {
+            if rng.gen_bool(0.5) {
+                for i in group.into_iter().flatten() {
+                    genes[i] = parent_b[i].clone();
+                }
+            }
+        }
+
+        genes.into_iter().collect()
+    }
+}
+
+/// Uniform crossover for parents of different neural topologies. The child
+/// takes the layout of a random parent; each gene comes from either parent
+/// when both have one at its (layer, neuron, input) coordinate, and from the
+/// layout's parent otherwise. Parents without layouts fall back to
+/// `UniformCrossover`.
+#[derive(Debug)]
+pub struct AlignedCrossover;
+
+impl<G: Clone> CrossoverMethod<G> for AlignedCrossover {
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        if parent_a.layout().is_none() || parent_b.layout().is_none() {
+            return UniformCrossover.crossover(rng, parent_a, parent_b);
+        }
+
+        let (base, other) = if rng.gen_bool(0.5) {(parent_a, parent_b)} else {(parent_b, parent_a)};
+        let aligned = base.layout().unwrap().align(other.layout().unwrap());
+
+        let mut child: Chromosome<G> = base
+            .iter()
+            .zip(aligned)
+            .map(|(gene, matching)| match matching {
+                Some(index) if rng.gen_bool(0.5) => other[index].clone(),
+                _ => gene.clone(),
+            })
+            .collect();
+        // Step sizes and bounds only line up with the base parent's genes
+        child.inherit_from(base, base);
+        child
+    }
+}
+
+// Random `start..end` slice of a chromosome of length `len`, at least one gene long
+fn random_segment(rng: &mut dyn RngCore, len: usize) -> (usize, usize) {
+    let a = rng.gen_range(0..len);
+    let b = rng.gen_range(0..len);
+    (a.min(b), a.max(b) + 1)
+}
+
+/// Partially mapped crossover (PMX) for permutations: copies a random slice
+/// of parent A and fills the rest from parent B, following the slice's
+/// mapping wherever B's gene is already taken. Parents that aren't
+/// permutations of each other come back as a copy of parent A.
+#[derive(Debug)]
+pub struct PartiallyMappedCrossover;
+
+impl<G> CrossoverMethod<G> for PartiallyMappedCrossover
+where
+    G: Clone + Eq + Hash,
+{
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        if parent_a.is_empty() || !is_permutation_of(parent_a, parent_b) {
+            return parent_a.clone();
+        }
+
+        let (start, end) = random_segment(rng, parent_a.len());
+        partially_mapped(parent_a, parent_b, start, end)
+    }
+}
+
+// Whether both chromosomes hold the same distinct genes, in any order
+fn is_permutation_of<G>(a: &Chromosome<G>, b: &Chromosome<G>) -> bool
+where
+    G: Eq + Hash,
+{
+    let genes: HashSet<&G> = a.iter().collect();
+    genes.len() == a.len() && genes == b.iter().collect()
+}
+
+fn partially_mapped<G>(parent_a: &Chromosome<G>, parent_b: &Chromosome<G>, start: usize, end: usize) -> Chromosome<G>
+where
+    G: Clone + Eq + Hash,
+{
+    let position_in_b: HashMap<&G, usize> = parent_b.iter().enumerate().map(|(i, gene)| (gene, i)).collect();
+
+    let mut genes: Vec<Option<G>> = vec![None; parent_a.len()];
+    for i in start..end {
+        genes[i] = Some(parent_a[i].clone());
+    }
+
+    let in_segment: HashSet<&G> = parent_a.as_slice()[start..end].iter().collect();
+    for i in start..end {
+        let gene = &parent_b[i];
+        if in_segment.contains(gene) {
+            continue;
+        }
+
+        // Follow a -> b until we land outside the copied slice
+        let mut slot = i;
+        while (start..end).contains(&slot) {
+            slot = position_in_b[&parent_a[slot]];
+        }
+        genes[slot] = Some(gene.clone());
+    }
+
+    genes
+        .into_iter()
+        .zip(parent_b.iter())
+        .map(|(gene, b)| gene.unwrap_or_else(|| b.clone()))
+        .collect()
+}
+
+/// Order crossover (OX1) for permutations: copies a random slice of parent A
+/// and fills the remaining positions with B's genes in the order they appear
+/// in B, starting after the slice. Parents that aren't permutations of each
+/// other come back as a copy of parent A.
+#[derive(Debug)]
+pub struct OrderCrossover;
+
+impl<G> CrossoverMethod<G> for OrderCrossover
+where
+    G: Clone + Eq + Hash,
+{
+    fn crossover(&self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome<G>,
+        parent_b: &Chromosome<G>
+    ) -> Chromosome<G> {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let len = parent_a.len();
+        if len == 0 || !is_permutation_of(parent_a, parent_b) {
+            return parent_a.clone();
+        }
+
+        let (start, end) = random_segment(rng, len);
+        let in_segment: HashSet<&G> = parent_a.as_slice()[start..end].iter().collect();
+
+        let mut rest = (0..len)
+            .map(|i| &parent_b[(end + i) % len])
+            .filter(|gene| !in_segment.contains(gene));
+
+        let mut genes: Vec<Option<G>> = vec![None; len];
+        for i in start..end {
+            genes[i] = Some(parent_a[i].clone());
+        }
+        for i in (0..len).map(|i| (end + i) % len).filter(|i| !(start..end).contains(i)) {
+            genes[i] = rest.next().cloned();
+        }
+
+        genes
+            .into_iter()
+            .map(|gene| gene.expect("Parents were checked to be permutations"))
             .collect()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parents() -> (Chromosome, Chromosome) {
+        let parent_a = (1..=100).map(|n| n as f32).collect();
+        let parent_b = (1..=100).map(|n| -n as f32).collect();
+        (parent_a, parent_b)
+    }
+
+    #[test]
+    fn uniform_takes_every_gene_from_a_parent() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = UniformCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        for i in 0..child.len() {
+            assert!(child[i] == parent_a[i] || child[i] == parent_b[i]);
+        }
+    }
+
+    #[test]
+    fn k_point_switches_parent_at_most_k_times() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        for points in [1, 2, 5] {
+            let child = KPointCrossover::new(points).crossover(&mut rng, &parent_a, &parent_b);
+
+            let from_a: Vec<bool> = (0..child.len())
+                .map(|i| {
+                    assert!(child[i] == parent_a[i] || child[i] == parent_b[i]);
+                    child[i] == parent_a[i]
+                })
+                .collect();
+            let switches = from_a.windows(2).filter(|pair| pair[0] != pair[1]).count();
+
+            assert_eq!(switches, points);
+        }
+    }
+
+    #[test]
+    fn arithmetic_children_conserve_gene_sum() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = ArithmeticCrossover::new(0.3).crossover(&mut rng, &parent_a, &parent_b);
+        let sibling = ArithmeticCrossover::new(0.7).crossover(&mut rng, &parent_a, &parent_b);
+
+        for i in 0..child.len() {
+            assert_relative_eq!(child[i] + sibling[i], parent_a[i] + parent_b[i], epsilon = 1e-4);
+            assert!(child[i] >= parent_b[i] && child[i] <= parent_a[i]);
+        }
+
+        let child = ArithmeticCrossover::random().crossover(&mut rng, &parent_a, &parent_b);
+        for i in 0..child.len() {
+            assert!(child[i] >= parent_b[i] && child[i] <= parent_a[i]);
+        }
+    }
+
+    #[test]
+    fn blend_stays_within_widened_interval() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        let child = BlendCrossover::new(0.5).crossover(&mut rng, &parent_a, &parent_b);
+
+        for i in 0..child.len() {
+            let margin = 0.5 * (parent_a[i] - parent_b[i]);
+            assert!(child[i] >= parent_b[i] - margin && child[i] <= parent_a[i] + margin);
+        }
+
+        let child = BlendCrossover::new(0.0).crossover(&mut rng, &parent_a, &parent_b);
+        for i in 0..child.len() {
+            assert!(child[i] >= parent_b[i] && child[i] <= parent_a[i]);
+        }
+    }
+
+    #[test]
+    fn structured_keeps_neurons_whole() {
+        use crate::gene_layout::{GeneKind, GeneLayout, GeneSegment};
+        use std::sync::Arc;
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (mut parent_a, parent_b) = parents();
+
+        // 10 neurons with 9 weights and a bias each, biases stored last
+        let layout: GeneLayout = (0..10)
+            .flat_map(|neuron| [
+                GeneSegment { layer: 0, neuron, kind: GeneKind::Weights, range: neuron * 9..(neuron + 1) * 9 },
+                GeneSegment { layer: 0, neuron, kind: GeneKind::Bias, range: 90 + neuron..91 + neuron },
+            ])
+            .collect();
+        let groups = layout.groups(Granularity::Neuron);
+        parent_a.set_layout(Arc::new(layout));
+
+        // Only one parent carries the layout, in either position
+        for (first, second) in [(&parent_a, &parent_b), (&parent_b, &parent_a)] {
+            let child = StructuredCrossover::new(Granularity::Neuron).crossover(&mut rng, first, second);
+
+            let mut from_a = 0;
+            for group in &groups {
+                let sources: Vec<bool> = group.iter().cloned().flatten().map(|i| child[i] == parent_a[i]).collect();
+                assert!(sources.iter().all(|&source| source == sources[0]));
+                from_a += sources[0] as usize;
+            }
+            assert!(from_a > 0 && from_a < 10);
+        }
+    }
+
+    #[test]
+    fn structured_aligns_different_topologies_of_equal_length() {
+        use crate::gene_layout::tests::dense_layout;
+        use std::sync::Arc;
+
+        // 13 genes each, but the neurons don't line up
+        let mut parent_a: Chromosome = (0..13).map(|gene| gene as f32).collect();
+        let mut parent_b: Chromosome = (0..13).map(|gene| -gene as f32).collect();
+        parent_a.set_layout(Arc::new(dense_layout(&[1, 4, 1])));
+        parent_b.set_layout(Arc::new(dense_layout(&[2, 3, 1])));
+
+        let structured = StructuredCrossover::new(Granularity::Neuron)
+            .crossover(&mut ChaCha8Rng::from_seed(Default::default()), &parent_a, &parent_b);
+        let aligned = AlignedCrossover.crossover(&mut ChaCha8Rng::from_seed(Default::default()), &parent_a, &parent_b);
+
+        assert_eq!(structured, aligned);
+    }
+
+    #[test]
+    fn aligned_mixes_topologies() {
+        use crate::gene_layout::tests::dense_layout;
+        use std::sync::Arc;
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (small_layout, large_layout) = (dense_layout(&[3, 4, 2]), dense_layout(&[5, 4, 2]));
+        let mut small: Chromosome = vec![1.0; small_layout.len()].into_iter().collect();
+        let mut large: Chromosome = vec![-1.0; large_layout.len()].into_iter().collect();
+        small.set_layout(Arc::new(small_layout));
+        large.set_layout(Arc::new(large_layout.clone()));
+        let unmatched: Vec<bool> = large_layout.align(small.layout().unwrap()).iter().map(Option::is_none).collect();
+
+        let children: Vec<Chromosome> = (0..20).map(|_| AlignedCrossover.crossover(&mut rng, &small, &large)).collect();
+        assert!(children.iter().any(|child| child.len() == small.len()));
+        assert!(children.iter().any(|child| child.len() == large.len()));
+
+        for child in children.iter().filter(|child| child.len() == large.len()) {
+            assert_eq!(child.layout(), large.layout());
+            assert!(child.iter().zip(&unmatched).all(|(&gene, &unmatched)| !unmatched || gene == -1.0));
+            assert!(child.iter().any(|&gene| gene == 1.0));
+        }
+    }
+
+    fn is_permutation(chromosome: &Chromosome<usize>) -> bool {
+        let mut genes = chromosome.as_slice().to_vec();
+        genes.sort_unstable();
+        genes == (0..chromosome.len()).collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn permutation_crossovers_yield_permutations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<usize> = (0..20).collect();
+        let parent_b: Chromosome<usize> = (0..20).rev().collect();
+
+        for _ in 0..100 {
+            let pmx = PartiallyMappedCrossover.crossover(&mut rng, &parent_a, &parent_b);
+            let ox = OrderCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+            assert!(is_permutation(&pmx));
+            assert!(is_permutation(&ox));
+        }
+    }
+
+    #[test]
+    fn pmx_matches_textbook_example() {
+        // Segment 3..7 copied from A; B's 8 and 2 are placed via the mapping
+        let parent_a: Chromosome<usize> = [1, 2, 3, 4, 5, 6, 7, 8, 9].into_iter().collect();
+        let parent_b: Chromosome<usize> = [9, 3, 7, 8, 2, 6, 5, 1, 4].into_iter().collect();
+
+        let child = partially_mapped(&parent_a, &parent_b, 3, 7);
+
+        assert_eq!(child.as_slice(), &[9, 3, 2, 4, 5, 6, 7, 1, 8]);
+    }
+
+    #[test]
+    fn permutation_crossovers_keep_non_permutations_intact() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<usize> = [0, 1, 1, 2].into_iter().collect();
+        let parent_b: Chromosome<usize> = [2, 1, 0, 3].into_iter().collect();
+
+        for _ in 0..20 {
+            assert_eq!(PartiallyMappedCrossover.crossover(&mut rng, &parent_a, &parent_b), parent_a);
+            assert_eq!(OrderCrossover.crossover(&mut rng, &parent_b, &parent_a), parent_b);
+        }
+    }
+
+    #[test]
+    fn uniform_works_on_bitstrings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome<bool> = vec![true; 100].into_iter().collect();
+        let parent_b: Chromosome<bool> = vec![false; 100].into_iter().collect();
+
+        let child = UniformCrossover.crossover(&mut rng, &parent_a, &parent_b);
+        let ones = child.iter().filter(|&&bit| bit).count();
+
+        assert!((35..65).contains(&ones));
+    }
+
+    #[test]
+    fn sbx_is_centred_on_parents() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a: Chromosome = vec![1.0; 1000].into_iter().collect();
+        let parent_b: Chromosome = vec![3.0; 1000].into_iter().collect();
+
+        let child = SimulatedBinaryCrossover::new(2.0).crossover(&mut rng, &parent_a, &parent_b);
+        let mean = child.iter().sum::<f32>() / child.len() as f32;
+
+        assert_relative_eq!(mean, 2.0, epsilon = 0.1);
+
+        // Identical parents have nothing to spread over
+        let child = SimulatedBinaryCrossover::new(2.0).crossover(&mut rng, &parent_a, &parent_a);
+        assert_eq!(child, parent_a);
+    }
+}
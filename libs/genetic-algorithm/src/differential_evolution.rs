@@ -0,0 +1,225 @@
+use crate::*;
+use lib_config::Config;
+
+pub use lib_config::{DeControl, DeStrategy};
+
+// jDE probabilities of drawing a fresh F or CR, and the range F is drawn from
+const TAU_F: f64 = 0.1;
+const TAU_CR: f64 = 0.1;
+const F_MIN: f32 = 0.1;
+const F_MAX: f32 = 1.0;
+
+#[derive(Clone, Debug)]
+struct Target {
+    chromosome: Chromosome,
+    fitness: f32,
+    f: f32,
+    cr: f32,
+}
+
+/// Differential evolution. Every individual is a target; each generation
+/// breeds one trial per target, and the trial replaces its target if it does
+/// at least as well.
+///
+/// Trials are evaluated by the caller between calls to `evolve`: pass the
+/// evaluated trials back in, in the order `evolve` returned them.
+#[derive(Clone, Debug)]
+pub struct DifferentialEvolution {
+    strategy: DeStrategy,
+    control: DeControl,
+    targets: Vec<Target>,
+    // F and CR each pending trial was bred with
+    trial_params: Vec<(f32, f32)>,
+}
+
+impl DifferentialEvolution {
+    pub fn new(strategy: DeStrategy, control: DeControl) -> Self {
+        if let DeControl::Fixed { f, cr } = control {
+            assert!(f > 0.0 && f <= 2.0);
+            assert!((0.0..=1.0).contains(&cr));
+        }
+        Self {
+            strategy,
+            control,
+            targets: Vec::new(),
+            trial_params: Vec::new(),
+        }
+    }
+
+    // jDE, Brest et al. 2006
+    pub fn self_adaptive(strategy: DeStrategy) -> Self {
+        Self::new(strategy, DeControl::SelfAdaptive)
+    }
+
+    // None unless `config.engine` asks for differential evolution
+    pub fn from_config(config: Config) -> Option<Self> {
+        match config.engine {
+            lib_config::Engine::DifferentialEvolution { strategy, control } => Some(Self::new(strategy, control)),
+            lib_config::Engine::Genetic => None,
+        }
+    }
+
+    // Best target found so far
+    pub fn best(&self) -> Option<(&Chromosome, f32)> {
+        self.targets
+            .iter()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+            .map(|target| (&target.chromosome, target.fitness))
+    }
+
+    pub fn evolve<I: Individual>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I> {
+        assert!(population.len() >= 4, "Differential evolution needs at least four individuals");
+
+        if self.targets.len() == population.len() && self.trial_params.len() == population.len() {
+            self.select(population);
+        } else {
+            let (f, cr) = match self.control {
+                DeControl::Fixed { f, cr } => (f, cr),
+                DeControl::SelfAdaptive => (0.5, 0.9),
+            };
+            self.targets = population
+                .iter()
+                .map(|individual| Target {
+                    chromosome: individual.chromosome().clone(),
+                    fitness: individual.fitness(),
+                    f,
+                    cr,
+                })
+                .collect();
+        }
+
+        self.trial_params.clear();
+        (0..self.targets.len())
+            .map(|index| I::create(self.trial(rng, index)))
+            .collect()
+    }
+
+    // Each trial competes against the target it was bred for
+    fn select<I: Individual>(&mut self, trials: &[I]) {
+        for ((target, trial), &(f, cr)) in self.targets.iter_mut().zip(trials).zip(&self.trial_params) {
+            if trial.fitness() >= target.fitness {
+                *target = Target {
+                    chromosome: trial.chromosome().clone(),
+                    fitness: trial.fitness(),
+                    f,
+                    cr,
+                };
+            }
+        }
+    }
+
+    fn trial(&mut self, rng: &mut dyn RngCore, index: usize) -> Chromosome {
+        let target = &self.targets[index];
+        let (f, cr) = match self.control {
+            DeControl::Fixed { f, cr } => (f, cr),
+            DeControl::SelfAdaptive => (
+                if rng.gen_bool(TAU_F) {rng.gen_range(F_MIN..F_MAX)} else {target.f},
+                if rng.gen_bool(TAU_CR) {rng.gen()} else {target.cr},
+            ),
+        };
+        self.trial_params.push((f, cr));
+
+        // Distinct from each other and from the target
+        let others: Vec<usize> = rand::seq::index::sample(rng, self.targets.len() - 1, 3)
+            .into_iter()
+            .map(|other| if other >= index {other + 1} else {other})
+            .collect();
+
+        let (base, a, b) = match self.strategy {
+            DeStrategy::Rand1Bin => (others[0], others[1], others[2]),
+            DeStrategy::Best1Bin => {
+                let best = (0..self.targets.len())
+                    .max_by(|&a, &b| self.targets[a].fitness.total_cmp(&self.targets[b].fitness))
+                    .unwrap();
+                (best, others[0], others[1])
+            }
+        };
+
+        let target = &self.targets[index].chromosome;
+        let base = &self.targets[base].chromosome;
+        let a = &self.targets[a].chromosome;
+        let b = &self.targets[b].chromosome;
+
+        // At least one gene always comes from the mutant
+        let forced = rng.gen_range(0..target.len());
+        let mut trial: Chromosome = (0..target.len())
+            .map(|gene| {
+                if gene == forced || rng.gen_bool(cr as f64) {
+                    base[gene] + f * (a[gene] - b[gene])
+                } else {
+                    target[gene]
+                }
+            })
+            .collect();
+        trial.inherit_from(target, target);
+        trial
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn evaluate(population: Vec<TestIndividual>) -> Vec<TestIndividual> {
+        population
+            .into_iter()
+            .map(|individual| {
+                let chromosome = individual.chromosome().clone();
+                let sphere: f32 = chromosome.iter().map(|gene| gene * gene).sum();
+                TestIndividual::with_chromosome(-sphere, chromosome)
+            })
+            .collect()
+    }
+
+    fn minimize(mut de: DifferentialEvolution) -> f32 {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population = evaluate(
+            (0..30)
+                .map(|_| TestIndividual::create((0..5).map(|_| rng.gen_range(-5.0..5.0)).collect()))
+                .collect(),
+        );
+
+        for _ in 0..300 {
+            population = evaluate(de.evolve(&mut rng, &population));
+        }
+        de.evolve(&mut rng, &population);
+
+        -de.best().unwrap().1
+    }
+
+    #[test]
+    fn rand_1_bin_converges() {
+        assert!(minimize(DifferentialEvolution::new(DeStrategy::Rand1Bin, DeControl::Fixed { f: 0.5, cr: 0.9 })) < 1e-4);
+    }
+
+    #[test]
+    fn best_1_bin_converges() {
+        assert!(minimize(DifferentialEvolution::new(DeStrategy::Best1Bin, DeControl::Fixed { f: 0.5, cr: 0.9 })) < 1e-4);
+    }
+
+    #[test]
+    fn jde_converges() {
+        assert!(minimize(DifferentialEvolution::self_adaptive(DeStrategy::Rand1Bin)) < 1e-4);
+    }
+
+    #[test]
+    fn worse_trials_are_rejected() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut de = DifferentialEvolution::new(DeStrategy::Rand1Bin, DeControl::Fixed { f: 0.5, cr: 0.5 });
+        let population: Vec<_> = (0..4)
+            .map(|i| TestIndividual::with_chromosome(i as f32, [i as f32].into_iter().collect()))
+            .collect();
+
+        let trials: Vec<_> = de
+            .evolve(&mut rng, &population)
+            .into_iter()
+            .map(|trial| TestIndividual::with_chromosome(-1.0, trial.chromosome().clone()))
+            .collect();
+        de.evolve(&mut rng, &trials);
+
+        let fitness: Vec<f32> = de.targets.iter().map(|target| target.fitness).collect();
+        assert_eq!(fitness, vec![0.0, 1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::*;
+use crate::chromosome::Gene;
+use crate::schedule::Parameter;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FitnessSummary {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub median: f32,
+    pub std_dev: f32,
+}
+
+impl FitnessSummary {
+    pub fn new(fitness: &[f32]) -> Self {
+        if fitness.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = fitness.to_vec();
+        sorted.sort_by(f32::total_cmp);
+
+        let len = sorted.len();
+        let mean = sorted.iter().sum::<f32>() / len as f32;
+        let variance = sorted.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / len as f32;
+        let median = if len % 2 == 0 {
+            0.5 * (sorted[len / 2 - 1] + sorted[len / 2])
+        } else {
+            sorted[len / 2]
+        };
+
+        Self {
+            min: sorted[0],
+            max: sorted[len - 1],
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diversity {
+    // Average `Chromosome::distance` over every pair of individuals
+    pub mean_pairwise_distance: f32,
+    // Population variance of each gene position; empty when chromosome
+    // lengths differ
+    pub gene_variance: Vec<f32>,
+}
+
+impl Diversity {
+    pub fn new<G: Gene>(chromosomes: &[&Chromosome<G>]) -> Self {
+        let len = chromosomes.len();
+        if len == 0 {
+            return Self::default();
+        }
+
+        let mut total = 0.0;
+        for (i, a) in chromosomes.iter().enumerate() {
+            for b in &chromosomes[i + 1..] {
+                total += a.distance(b);
+            }
+        }
+        let pairs = len * (len - 1) / 2;
+        let mean_pairwise_distance = if pairs == 0 {0.0} else {total / pairs as f32};
+
+        // Per-gene variance only makes sense when every gene lines up
+        let num_genes = chromosomes[0].len();
+        if chromosomes.iter().any(|chromosome| chromosome.len() != num_genes) {
+            return Self {
+                mean_pairwise_distance,
+                gene_variance: Vec::new(),
+            };
+        }
+        let gene_variance = (0..num_genes)
+            .map(|gene| {
+                let mean = chromosomes.iter().map(|c| c[gene].value()).sum::<f32>() / len as f32;
+                chromosomes.iter().map(|c| (c[gene].value() - mean).powi(2)).sum::<f32>() / len as f32
+            })
+            .collect();
+
+        Self {
+            mean_pairwise_distance,
+            gene_variance,
+        }
+    }
+
+    pub fn mean_gene_variance(&self) -> f32 {
+        if self.gene_variance.is_empty() {
+            0.0
+        } else {
+            self.gene_variance.iter().sum::<f32>() / self.gene_variance.len() as f32
+        }
+    }
+}
+
+/// What happened during one call to `GeneticAlgorithm::evolve`. Fitness and
+/// diversity describe the population that was evolved, not the children.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EvolutionReport {
+    pub fitness: FitnessSummary,
+    // Left empty unless the engine was asked for it, as it's costly
+    pub diversity: Diversity,
+    // Genes changed by mutation, summed over every child; 0 unless the
+    // engine was asked to count
+    pub mutated_genes: usize,
+    // How many times each individual was picked as a parent
+    pub parent_usage: Vec<usize>,
+    // Values the genetic algorithm's schedules set for this generation,
+    // leaving out parameters its operators don't have
+    pub parameters: Vec<(Parameter, f32)>,
+    // Fraction of the previous generation's children that beat the fitter
+    // of their parents, if known
+    pub success_rate: Option<f32>,
+}
+
+impl EvolutionReport {
+    // Just the fitness of `population`; the rest, diversity included, is
+    // left for the engine to fill in
+    pub fn of_population<I: Individual<G>, G>(population: &[I]) -> Self {
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+
+        Self {
+            fitness: FitnessSummary::new(&fitness),
+            diversity: Diversity::default(),
+            mutated_genes: 0,
+            parent_usage: vec![0; population.len()],
+            parameters: Vec::new(),
+            success_rate: None,
+        }
+    }
+
+    // Entry `k` is the number of individuals picked as a parent exactly `k` times
+    pub fn parent_usage_histogram(&self) -> Vec<usize> {
+        let max = self.parent_usage.iter().copied().max().unwrap_or(0);
+        let mut histogram = vec![0; max + 1];
+        for &count in &self.parent_usage {
+            histogram[count] += 1;
+        }
+        histogram
+    }
+
+    // Individuals that were never picked as a parent
+    pub fn unused_parents(&self) -> usize {
+        self.parent_usage.iter().filter(|&&count| count == 0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn fitness_summary() {
+        let summary = FitnessSummary::new(&[4.0, 1.0, 3.0, 2.0]);
+
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_relative_eq!(summary.std_dev, 1.25f32.sqrt());
+    }
+
+    #[test]
+    fn diversity_of_identical_population_is_zero() {
+        let a: Chromosome = [1.0, 2.0].into_iter().collect();
+        let b: Chromosome = [3.0, 2.0].into_iter().collect();
+
+        let same = Diversity::new(&[&a, &a, &a]);
+        assert_eq!(same.mean_pairwise_distance, 0.0);
+        assert_eq!(same.gene_variance, vec![0.0, 0.0]);
+
+        let diverse = Diversity::new(&[&a, &b]);
+        assert_eq!(diverse.mean_pairwise_distance, a.distance(&b));
+        assert_eq!(diverse.gene_variance, vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn parent_usage_histogram() {
+        let report = EvolutionReport {
+            parent_usage: vec![0, 2, 1, 0, 2, 5],
+            ..Default::default()
+        };
+
+        assert_eq!(report.parent_usage_histogram(), vec![2, 1, 2, 0, 0, 1]);
+        assert_eq!(report.unused_parents(), 2);
+    }
+}
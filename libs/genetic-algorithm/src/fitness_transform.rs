@@ -0,0 +1,149 @@
+/// Maps raw fitness values onto selection weights. Wrap a selection method in
+/// `selection_method::Scaled` to apply one; tuples chain left to right.
+pub trait FitnessTransform {
+    fn transform(&self, fitness: &[f32]) -> Vec<f32>;
+}
+
+/// Shifts every value so the worst individual ends up at `floor`.
+/// Handles negative fitness and a population that is uniformly zero, which
+/// is why `floor` must be positive.
+#[derive(Debug)]
+pub struct Offset {
+    floor: f32,
+}
+
+impl Offset {
+    pub fn new(floor: f32) -> Self {
+        assert!(floor > 0.0);
+        Self {
+            floor,
+        }
+    }
+}
+
+impl FitnessTransform for Offset {
+    fn transform(&self, fitness: &[f32]) -> Vec<f32> {
+        let min = fitness.iter().copied().fold(f32::INFINITY, f32::min);
+
+        fitness.iter().map(|f| f - min + self.floor).collect()
+    }
+}
+
+/// Sigma scaling: `1 + (f - mean) / (c * std_dev)`, clipped at zero. Keeps
+/// selection pressure steady whether fitness is spread out or bunched up.
+#[derive(Debug)]
+pub struct SigmaScaling {
+    c: f32,
+}
+
+impl SigmaScaling {
+    pub fn new(c: f32) -> Self {
+        assert!(c > 0.0);
+        Self {
+            c,
+        }
+    }
+}
+
+impl Default for SigmaScaling {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+impl FitnessTransform for SigmaScaling {
+    fn transform(&self, fitness: &[f32]) -> Vec<f32> {
+        let len = fitness.len() as f32;
+        let mean = fitness.iter().sum::<f32>() / len;
+        let std_dev = (fitness.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / len).sqrt();
+
+        if std_dev == 0.0 {
+            return vec![1.0; fitness.len()];
+        }
+
+        fitness
+            .iter()
+            .map(|f| (1.0 + (f - mean) / (self.c * std_dev)).max(0.0))
+            .collect()
+    }
+}
+
+/// Replaces each value by its rank, 1 for the worst up to `len` for the best.
+#[derive(Debug)]
+pub struct RankMapping;
+
+impl FitnessTransform for RankMapping {
+    fn transform(&self, fitness: &[f32]) -> Vec<f32> {
+        let mut ranked: Vec<usize> = (0..fitness.len()).collect();
+        ranked.sort_by(|&a, &b| fitness[a].total_cmp(&fitness[b]));
+
+        let mut weights = vec![0.0; fitness.len()];
+        for (rank, index) in ranked.into_iter().enumerate() {
+            weights[index] = (rank + 1) as f32;
+        }
+        weights
+    }
+}
+
+/// Leaves usable weights alone, but falls back to equal weights when no
+/// individual has a positive, finite one (e.g. nobody has eaten yet).
+#[derive(Debug)]
+pub struct UniformFallback;
+
+impl FitnessTransform for UniformFallback {
+    fn transform(&self, fitness: &[f32]) -> Vec<f32> {
+        let usable = fitness.iter().all(|f| f.is_finite() && *f >= 0.0)
+            && fitness.iter().any(|f| *f > 0.0);
+
+        if usable {
+            fitness.to_vec()
+        } else {
+            vec![1.0; fitness.len()]
+        }
+    }
+}
+
+impl<A, B> FitnessTransform for (A, B)
+where
+    A: FitnessTransform,
+    B: FitnessTransform,
+{
+    fn transform(&self, fitness: &[f32]) -> Vec<f32> {
+        self.1.transform(&self.0.transform(fitness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn offset_moves_minimum_to_floor() {
+        let weights = Offset::new(0.5).transform(&[-2.0, 0.0, 3.0]);
+
+        assert_eq!(weights, vec![0.5, 2.5, 5.5]);
+        assert_eq!(Offset::new(0.5).transform(&[0.0, 0.0]), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn sigma_scaling_centres_on_one() {
+        let weights = SigmaScaling::new(1.0).transform(&[1.0, 3.0]);
+
+        assert_relative_eq!(weights[0], 0.0);
+        assert_relative_eq!(weights[1], 2.0);
+        assert_eq!(SigmaScaling::default().transform(&[0.0, 0.0]), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn rank_mapping() {
+        assert_eq!(RankMapping.transform(&[5.0, -1.0, 10.0]), vec![2.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn uniform_fallback() {
+        assert_eq!(UniformFallback.transform(&[0.0, 0.0]), vec![1.0, 1.0]);
+        assert_eq!(UniformFallback.transform(&[0.0, f32::NAN]), vec![1.0, 1.0]);
+        assert_eq!(UniformFallback.transform(&[0.0, 2.0]), vec![0.0, 2.0]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GeneKind {
+    Weights,
+    Bias,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneSegment {
+    pub layer: usize,
+    pub neuron: usize,
+    pub kind: GeneKind,
+    pub range: Range<usize>,
+}
+
+// Where a single gene sits in the network; `input` is 0 for biases
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GeneCoordinate {
+    pub layer: usize,
+    pub neuron: usize,
+    pub kind: GeneKind,
+    pub input: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Granularity {
+    Neuron,
+    Layer,
+}
+
+/// Describes which genes of a neural chromosome belong to which layer and
+/// neuron, so operators can treat them as units instead of loose floats.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneLayout {
+    segments: Vec<GeneSegment>,
+}
+
+impl GeneLayout {
+    pub fn segments(&self) -> &[GeneSegment] {
+        &self.segments
+    }
+
+    // Total number of genes described
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(|segment| segment.range.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Gene ranges that belong together at the given granularity, in layout order
+    pub fn groups(&self, granularity: Granularity) -> Vec<Vec<Range<usize>>> {
+        let mut groups: BTreeMap<(usize, usize), Vec<Range<usize>>> = BTreeMap::new();
+
+        for segment in &self.segments {
+            let key = match granularity {
+                Granularity::Neuron => (segment.layer, segment.neuron),
+                Granularity::Layer => (segment.layer, 0),
+            };
+            groups.entry(key).or_default().push(segment.range.clone());
+        }
+
+        groups.into_values().collect()
+    }
+
+    // Every gene's coordinate along with its index
+    pub fn coordinates(&self) -> impl Iterator<Item = (GeneCoordinate, usize)> + '_ {
+        self.segments.iter().flat_map(|segment| {
+            segment.range.clone().enumerate().map(move |(input, index)| {
+                let coordinate = GeneCoordinate {
+                    layer: segment.layer,
+                    neuron: segment.neuron,
+                    kind: segment.kind,
+                    input,
+                };
+                (coordinate, index)
+            })
+        })
+    }
+
+    // For each gene, the index of the gene at the same coordinate in
+    // `other`, if it has one
+    pub fn align(&self, other: &GeneLayout) -> Vec<Option<usize>> {
+        let lookup: HashMap<GeneCoordinate, usize> = other.coordinates().collect();
+        let mut aligned = vec![None; self.len()];
+        for (coordinate, index) in self.coordinates() {
+            aligned[index] = lookup.get(&coordinate).copied();
+        }
+        aligned
+    }
+
+    // Inputs of the first layer followed by the neuron count of every layer,
+    // i.e. the topology the genes were taken from
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        let mut layers: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for segment in &self.segments {
+            if segment.kind == GeneKind::Weights {
+                let (inputs, neurons) = layers.entry(segment.layer).or_default();
+                *inputs = segment.range.len();
+                *neurons = (*neurons).max(segment.neuron + 1);
+            }
+        }
+
+        let inputs = layers.values().next().map(|&(inputs, _)| inputs);
+        inputs.into_iter().chain(layers.values().map(|&(_, neurons)| neurons)).collect()
+    }
+}
+
+impl FromIterator<GeneSegment> for GeneLayout {
+    fn from_iter<T: IntoIterator<Item = GeneSegment>>(iter: T) -> Self {
+        Self {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Fully connected `inputs -> neurons` layers, weights before biases
+    pub(crate) fn dense_layout(sizes: &[usize]) -> GeneLayout {
+        let mut offset = 0;
+        let mut segments = Vec::new();
+
+        for (layer, pair) in sizes.windows(2).enumerate() {
+            let (inputs, neurons) = (pair[0], pair[1]);
+            let biases = offset + inputs * neurons;
+            for neuron in 0..neurons {
+                let weights = offset + neuron * inputs;
+                segments.push(GeneSegment { layer, neuron, kind: GeneKind::Weights, range: weights..weights + inputs });
+                segments.push(GeneSegment { layer, neuron, kind: GeneKind::Bias, range: biases + neuron..biases + neuron + 1 });
+            }
+            offset = biases + neurons;
+        }
+
+        segments.into_iter().collect()
+    }
+
+    #[test]
+    fn aligns_by_coordinate() {
+        let small = dense_layout(&[1, 2, 1]);
+        let large = dense_layout(&[2, 2, 1]);
+        assert_eq!(small.layer_sizes(), vec![1, 2, 1]);
+        assert_eq!(large.layer_sizes(), vec![2, 2, 1]);
+
+        // Genes in order, as w<neuron><input> and b<neuron>, second layer primed
+        // Small: w00 w10 b0 b1 | w'00 w'01 b'0
+        // Large: w00 w01 w10 w11 b0 b1 | w'00 w'01 b'0
+        assert_eq!(small.align(&large), vec![Some(0), Some(2), Some(4), Some(5), Some(6), Some(7), Some(8)]);
+        assert_eq!(large.align(&small), vec![Some(0), None, Some(1), None, Some(2), Some(3), Some(4), Some(5), Some(6)]);
+    }
+
+    #[test]
+    fn distance_aligns_topologies_of_equal_length() {
+        use crate::chromosome::Chromosome;
+        use std::sync::Arc;
+
+        // 13 genes each; by index they'd be identical
+        let mut a: Chromosome = (0..13).map(|gene| gene as f32).collect();
+        let mut b = a.clone();
+        a.set_layout(Arc::new(dense_layout(&[1, 4, 1])));
+        b.set_layout(Arc::new(dense_layout(&[2, 3, 1])));
+
+        assert!(a.distance(&b) > 0.0);
+        assert_eq!(a.distance(&a.clone()), 0.0);
+    }
+}
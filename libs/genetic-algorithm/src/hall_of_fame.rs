@@ -0,0 +1,136 @@
+use crate::*;
+
+#[derive(Clone, Debug)]
+pub struct HallOfFameEntry<G = f32> {
+    fitness: f32,
+    chromosome: Chromosome<G>,
+}
+
+impl<G> HallOfFameEntry<G> {
+    pub fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    pub fn chromosome(&self) -> &Chromosome<G> {
+        &self.chromosome
+    }
+}
+
+/// The best chromosomes ever recorded, kept across generations. Unlike
+/// elitism it remembers individuals even after they drop out of the population.
+#[derive(Clone, Debug)]
+pub struct HallOfFame<G = f32> {
+    capacity: usize,
+    // Sorted best first
+    entries: Vec<HallOfFameEntry<G>>,
+}
+
+impl<G> HallOfFame<G>
+where
+    G: Clone + PartialEq,
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn best(&self) -> Option<&HallOfFameEntry<G>> {
+        self.entries.first()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HallOfFameEntry<G>> {
+        self.entries.iter()
+    }
+
+    // Call once per generation, before the population is replaced
+    pub fn record<I: Individual<G>>(&mut self, population: &[I]) {
+        for individual in fittest(population, self.capacity) {
+            self.insert(individual.fitness(), individual.chromosome());
+        }
+    }
+
+    fn insert(&mut self, fitness: f32, chromosome: &Chromosome<G>) {
+        if !fitness.is_finite() {
+            return;
+        }
+
+        // Elites come back every generation; keep just their best score
+        if let Some(existing) = self.entries.iter().position(|entry| &entry.chromosome == chromosome) {
+            if self.entries[existing].fitness >= fitness {
+                return;
+            }
+            self.entries.remove(existing);
+        }
+
+        if self.entries.len() == self.capacity
+            && self.entries.last().is_some_and(|worst| worst.fitness >= fitness)
+        {
+            return;
+        }
+
+        let at = self.entries.partition_point(|entry| entry.fitness >= fitness);
+        self.entries.insert(at, HallOfFameEntry {
+            fitness,
+            chromosome: chromosome.clone(),
+        });
+        self.entries.truncate(self.capacity);
+    }
+
+    // Replaces the `count` weakest members of `population` with the best
+    // hall-of-fame chromosomes
+    pub fn reinject<I: Individual<G>>(&self, population: &mut [I], count: usize) {
+        let mut ranked: Vec<usize> = (0..population.len()).collect();
+        ranked.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+
+        for (index, entry) in ranked.into_iter().zip(self.entries.iter().take(count)) {
+            population[index] = I::create(entry.chromosome.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn individual(fitness: f32, gene: f32) -> TestIndividual {
+        TestIndividual::with_chromosome(fitness, [gene].into_iter().collect())
+    }
+
+    #[test]
+    fn keeps_best_ever_seen() {
+        let mut hall_of_fame = HallOfFame::new(2);
+
+        hall_of_fame.record(&[individual(3.0, 0.0), individual(1.0, 1.0), individual(5.0, 2.0)]);
+        hall_of_fame.record(&[individual(4.0, 3.0), individual(0.0, 4.0)]);
+        // Same genome as before with a worse score shouldn't displace it
+        hall_of_fame.record(&[individual(2.0, 2.0)]);
+
+        let fitness: Vec<_> = hall_of_fame.iter().map(|entry| entry.fitness()).collect();
+        assert_eq!(fitness, vec![5.0, 4.0]);
+        assert_eq!(hall_of_fame.best().unwrap().chromosome()[0], 2.0);
+    }
+
+    #[test]
+    fn reinject_replaces_weakest() {
+        let mut hall_of_fame = HallOfFame::new(4);
+        hall_of_fame.record(&[individual(10.0, 7.0)]);
+
+        let mut population = vec![individual(3.0, 0.0), individual(1.0, 1.0), individual(2.0, 2.0)];
+        hall_of_fame.reinject(&mut population, 1);
+
+        assert_eq!(population[0].chromosome()[0], 0.0);
+        assert_eq!(population[1].chromosome()[0], 7.0);
+        assert_eq!(population[2].chromosome()[0], 2.0);
+    }
+}
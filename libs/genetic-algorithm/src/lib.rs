@@ -2,58 +2,943 @@ pub mod chromosome;
 pub mod selection_method;
 pub mod crossover_method;
 pub mod mutation_method;
+pub mod fitness_transform;
+pub mod hall_of_fame;
+pub mod gene_layout;
+pub mod nsga2;
+pub mod speciation;
+pub mod evolution_report;
+pub mod termination;
+pub mod observer;
+pub mod replacement;
+pub mod cma_es;
+pub mod differential_evolution;
+pub mod novelty;
+pub mod map_elites;
+pub mod bounds;
+pub mod memetic;
+pub mod schedule;
 
-use rand::{Rng,RngCore};
+use rand::{Rng,RngCore,SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
 use rand::seq::SliceRandom;
-use chromosome::Chromosome;
-use selection_method::SelectionMethod;
+use chromosome::{Chromosome, Gene};
+use evolution_report::{Diversity, EvolutionReport};
+use selection_method::{SelectionError, SelectionMethod};
 use crossover_method::CrossoverMethod;
 use mutation_method::MutationMethod;
+use observer::EvolutionObserver;
+use replacement::{Generational, ReplacementStrategy};
+use bounds::BoundsRepair;
+use memetic::{Inheritance, LocalSearch};
+use schedule::{Parameter, Schedule};
 
-pub trait Individual {
+pub trait Individual<G = f32> {
     fn fitness(&self) ->f32;
-    fn chromosome(&self) -> &Chromosome;
-    fn create(chromosome: Chromosome) -> Self;
+    fn chromosome(&self) -> &Chromosome<G>;
+    fn create(chromosome: Chromosome<G>) -> Self;
 }
 
-pub struct GeneticAlgorithm<S> {
+type Penalty<G> = dyn Fn(&Chromosome<G>) -> f32 + Send + Sync;
+pub(crate) type Repair<G> = dyn Fn(&mut Chromosome<G>) + Send + Sync;
+
+// Report figures that read gene values, see `with_gene_statistics`
+struct GeneStatistics<G> {
+    diversity: fn(&[&Chromosome<G>]) -> Diversity,
+    changed_genes: fn(&Chromosome<G>, &Chromosome<G>) -> usize,
+}
+
+pub struct GeneticAlgorithm<S, G = f32> {
     selection_method: S,
-    crossover_method: Box<dyn CrossoverMethod>,
-    mutation_method: Box<dyn MutationMethod>,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+    elitism: usize,
+    replacement: Box<dyn ReplacementStrategy>,
+    repair: Option<Box<Repair<G>>>,
+    // Subtracted from each individual's fitness before selection
+    penalty: Option<Box<Penalty<G>>>,
+    local_search: Option<LocalSearch<G>>,
+    schedules: Vec<(Parameter, Box<dyn Schedule>)>,
+    gene_statistics: Option<GeneStatistics<G>>,
+    // Fitness of the fitter parent of each slot of the last population bred,
+    // NaN for survivors; for the success rate fed to schedules
+    parent_fitness: Vec<f32>,
+    // Unrefined genes of each slot of the last population bred, where
+    // Baldwinian local search handed back refined ones
+    genotypes: Vec<Option<Chromosome<G>>>,
+    observers: Vec<Box<dyn EvolutionObserver>>,
+    // Number of finished `evolve` calls
+    generation: usize,
 }
 
-impl<S> GeneticAlgorithm<S>
+impl<S, G> GeneticAlgorithm<S, G>
 where 
-    S: SelectionMethod
+    S: SelectionMethod,
+    G: Clone,
 {
     pub fn new(selection_method: S,
-        crossover_method: impl CrossoverMethod + 'static,
-        mutation_method: impl MutationMethod + 'static,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
     ) -> Self {
         Self {
             selection_method,
             crossover_method: Box::new(crossover_method),
-            mutation_method: Box::new(mutation_method)
+            mutation_method: Box::new(mutation_method),
+            elitism: 0,
+            replacement: Box::new(Generational),
+            repair: None,
+            penalty: None,
+            local_search: None,
+            schedules: Vec::new(),
+            gene_statistics: None,
+            parent_fitness: Vec::new(),
+            genotypes: Vec::new(),
+            observers: Vec::new(),
+            generation: 0,
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
+    // Copy the `count` fittest chromosomes into the next generation untouched
+    pub fn with_elitism(mut self, count: usize) -> Self {
+        self.elitism = count;
+        self
+    }
+
+    pub fn with_selection_method(mut self, selection_method: S) -> Self {
+        self.selection_method = selection_method;
+        self
+    }
+
+    // Generational by default; elites survive on top of whatever the
+    // strategy keeps
+    pub fn with_replacement(mut self, replacement: impl ReplacementStrategy + 'static) -> Self {
+        self.replacement = Box::new(replacement);
+        self
+    }
+
+    // Selection sees `fitness - penalty(chromosome)`, e.g. to punish bound
+    // violations softly instead of repairing them; the selection method has
+    // to cope with the resulting negative values
+    pub fn with_penalty(mut self, penalty: impl Fn(&Chromosome<G>) -> f32 + Send + Sync + 'static) -> Self {
+        self.penalty = Some(Box::new(penalty));
+        self
+    }
+
+    // Refine every child after breeding, scoring candidates with the local
+    // search's own evaluation. Children come back refined either way; under
+    // Baldwinian inheritance the next generation still breeds from the
+    // unrefined genes, which assumes each population comes back in the order
+    // `evolve` returned it.
+    pub fn with_local_search(mut self, local_search: LocalSearch<G>) -> Self {
+        self.local_search = Some(local_search);
+        self
+    }
+
+    // Set `parameter` from `schedule` at the start of every generation, if
+    // the selection or mutation method has it. The success rate schedules
+    // see assumes each population comes back in the order `evolve` returned
+    // it; see `forget_lineage`.
+    pub fn with_schedule(mut self, parameter: Parameter, schedule: impl Schedule + 'static) -> Self {
+        self.schedules.push((parameter, Box::new(schedule)));
+        self
+    }
+
+    pub fn with_observer(mut self, observer: impl EvolutionObserver + 'static) -> Self {
+        self.add_observer(observer);
+        self
+    }
+
+    // Observers are notified in the order they were added
+    pub fn add_observer(&mut self, observer: impl EvolutionObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // For engines that breed in place of `evolve`, so observers still hear
+    // about every generation: `fitness` is what the engine bred on, and
+    // `report` what came of it
+    pub fn observe_generation(&mut self, fitness: &[f32], report: &EvolutionReport) {
+        for observer in &mut self.observers {
+            observer.on_generation_start(self.generation, fitness);
+            observer.on_generation_end(self.generation, report);
+        }
+        self.generation += 1;
+    }
+
+    // Call after changing a population between `evolve` calls, e.g. swapping
+    // in immigrants, so the success rate and Baldwinian genes aren't matched
+    // to slots that now hold someone else
+    pub fn forget_lineage(&mut self) {
+        self.parent_fitness.clear();
+        self.genotypes.clear();
+    }
+
+    // See `MutationMethod::set_boost`
+    pub fn set_mutation_boost(&mut self, boost: f32) {
+        self.mutation_method.set_boost(boost);
+    }
+
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, EvolutionReport), SelectionError>
     where 
-        I: Individual,
+        I: Individual<G>,
+    {
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+        self.evolve_with_fitness(rng, population, &fitness)
+    }
+
+    // Like `evolve`, but selects parents on `fitness` rather than each
+    // individual's own, e.g. after fitness sharing between species. Elites
+    // and survivors are still picked on their own fitness.
+    pub fn evolve_with_fitness<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitness: &[f32],
+    ) -> Result<(Vec<I>, EvolutionReport), SelectionError>
+    where
+        I: Individual<G>,
+    {
+        self.evolve_by(rng, population, fitness, |rng, operators, pairs| {
+            pairs
+                .into_iter()
+                .map(|(parent_a, parent_b)| operators.breed(rng, parent_a, parent_b))
+                .collect()
+        })
+    }
+
+    // Like `evolve`, but breeds children on rayon's thread pool. Every child
+    // gets its own ChaCha stream seeded from `rng`, so the result depends
+    // only on `rng` and never on the number of threads.
+    pub fn evolve_parallel<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> Result<(Vec<I>, EvolutionReport), SelectionError>
+    where
+        I: Individual<G>,
+        G: Send + Sync,
+    {
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+        self.evolve_parallel_with_fitness(rng, population, &fitness)
+    }
+
+    pub fn evolve_parallel_with_fitness<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitness: &[f32],
+    ) -> Result<(Vec<I>, EvolutionReport), SelectionError>
+    where
+        I: Individual<G>,
+        G: Send + Sync,
+    {
+        self.evolve_by(rng, population, fitness, |rng, operators, pairs| {
+            let seed: <ChaCha8Rng as SeedableRng>::Seed = rng.gen();
+
+            pairs
+                .into_par_iter()
+                .enumerate()
+                .map(|(slot, (parent_a, parent_b))| {
+                    let mut rng = ChaCha8Rng::from_seed(seed);
+                    rng.set_stream(slot as u64);
+                    operators.breed(&mut rng, parent_a, parent_b)
+                })
+                .collect()
+        })
+    }
+
+    // Shared by the sequential and parallel paths, which differ only in how
+    // `breed_all` turns parent pairs into offspring
+    fn evolve_by<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+        fitness: &[f32],
+        breed_all: impl for<'c> FnOnce(
+            &mut dyn RngCore,
+            &Operators<G>,
+            Vec<(&'c Chromosome<G>, &'c Chromosome<G>)>,
+        ) -> Vec<Offspring<G>>,
+    ) -> Result<(Vec<I>, EvolutionReport), SelectionError>
+    where
+        I: Individual<G>,
     {
-        assert!(!population.is_empty());
-
-        (0..population.len())
-            .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
-                //crossover
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
-                //mutation
-                self.mutation_method.mutate(rng, &mut child);
-
-                I::create(child)
+        assert_eq!(population.len(), fitness.len());
+
+        if population.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        // Breed from the genes Baldwinian search kept back, if any
+        let mut genotypes = std::mem::take(&mut self.genotypes);
+        if genotypes.len() != population.len() {
+            genotypes = vec![None; population.len()];
+        }
+        let chromosomes: Vec<&Chromosome<G>> = population
+            .iter()
+            .zip(&genotypes)
+            .map(|(individual, genotype)| genotype.as_ref().unwrap_or(individual.chromosome()))
+            .collect();
+
+        // Survival goes by each individual's own fitness and breeding by
+        // `fitness`, so sharing within a crowded species can't cost the
+        // best individual its place
+        let own: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+        let penalize = |fitness: &[f32]| -> Vec<f32> {
+            match &self.penalty {
+                Some(penalty) => fitness
+                    .iter()
+                    .zip(&chromosomes)
+                    .map(|(fitness, chromosome)| fitness - penalty(chromosome))
+                    .collect(),
+                None => fitness.to_vec(),
+            }
+        };
+        let (own, fitness) = (penalize(&own), penalize(fitness));
+        let fitness = &fitness[..];
+
+        let success_rate = self.success_rate(fitness);
+        let parameters = self.apply_schedules(success_rate);
+
+        for observer in &mut self.observers {
+            observer.on_generation_start(self.generation, fitness);
+        }
+
+        let mut survivors = best_first(&own);
+        survivors.truncate(self.elitism);
+        for index in self.replacement.survivors(&own) {
+            if !survivors.contains(&index) {
+                survivors.push(index);
+            }
+        }
+        let num_children = population.len() - survivors.len();
+        self.genotypes = survivors
+            .iter()
+            .map(|&index| genotypes[index].clone())
+            .collect();
+        let survivors = survivors
+            .into_iter()
+            .map(|index| I::create(population[index].chromosome().clone()));
+
+        let pool = self.replacement.parents(fitness);
+        let pool_fitness: Vec<f32> = pool.iter().map(|&index| fitness[index]).collect();
+        let parents: Vec<usize> = self.selection_method
+            .select_indices(rng, &pool_fitness, 2 * num_children)?
+            .into_iter()
+            .map(|selected| pool[selected])
+            .collect();
+        for observer in &mut self.observers {
+            observer.on_selection(self.generation, &parents);
+        }
+
+        let mut parent_usage = vec![0; population.len()];
+        for &parent in &parents {
+            parent_usage[parent] += 1;
+        }
+
+        let pairs = parents
+            .chunks(2)
+            .map(|pair| (chromosomes[pair[0]], chromosomes[pair[1]]))
+            .collect();
+
+        let mut mutated_genes = 0;
+        let operators = Operators {
+            crossover_method: &*self.crossover_method,
+            mutation_method: &*self.mutation_method,
+            repair: self.repair.as_deref(),
+            statistics: self.gene_statistics.as_ref(),
+            local_search: self.local_search.as_ref(),
+        };
+        self.parent_fitness = vec![f32::NAN; population.len() - num_children];
+        self.parent_fitness.extend(parents.chunks(2).map(|pair| fitness[pair[0]].max(fitness[pair[1]])));
+
+        let children: Vec<I> = breed_all(rng, &operators, pairs)
+            .into_iter()
+            .map(|offspring| {
+                mutated_genes += offspring.mutated_genes;
+                self.genotypes.push(offspring.genotype);
+                I::create(offspring.chromosome)
+            })
+            .collect();
+
+        let diversity = match &self.gene_statistics {
+            Some(statistics) => (statistics.diversity)(&population.iter().map(|individual| individual.chromosome()).collect::<Vec<_>>()),
+            None => Diversity::default(),
+        };
+        let report = EvolutionReport {
+            diversity,
+            mutated_genes,
+            parent_usage,
+            parameters,
+            success_rate,
+            ..EvolutionReport::of_population(population)
+        };
+
+        for observer in &mut self.observers {
+            observer.on_generation_end(self.generation, &report);
+        }
+        self.generation += 1;
+
+        Ok((survivors.chain(children).collect(), report))
+    }
+
+    fn success_rate(&self, fitness: &[f32]) -> Option<f32> {
+        if self.parent_fitness.len() != fitness.len() {
+            return None;
+        }
+
+        let (children, successes) = self.parent_fitness
+            .iter()
+            .zip(fitness)
+            .filter(|(parent, _)| !parent.is_nan())
+            .fold((0, 0), |(children, successes), (parent, child)| {
+                (children + 1, successes + (child > parent) as usize)
+            });
+
+        (children > 0).then(|| successes as f32 / children as f32)
+    }
+
+    // Values actually set; parameters the operators lack are left out
+    fn apply_schedules(&mut self, success_rate: Option<f32>) -> Vec<(Parameter, f32)> {
+        self.schedules
+            .iter_mut()
+            .filter_map(|(parameter, schedule)| {
+                let value = schedule.value(self.generation, success_rate);
+                let applied = match parameter {
+                    Parameter::MutationChance => self.mutation_method.set_chance(value),
+                    Parameter::MutationCoeff => self.mutation_method.set_coeff(value),
+                    Parameter::SelectionPressure => self.selection_method.set_pressure(value),
+                };
+                applied.then_some((*parameter, value))
             })
             .collect()
     }
-}
\ No newline at end of file
+}
+
+impl<S, G> GeneticAlgorithm<S, G>
+where
+    S: SelectionMethod,
+    G: Gene,
+{
+    // Repair every child after crossover and mutation
+    pub fn with_repair(mut self, repair: BoundsRepair) -> Self {
+        self.repair = Some(Box::new(move |chromosome| repair.apply(chromosome)));
+        self
+    }
+
+    // Fill in the report's diversity and mutated gene count. Diversity
+    // compares every pair of individuals, so this costs O(N²·L) per
+    // generation.
+    pub fn with_gene_statistics(mut self) -> Self {
+        self.gene_statistics = Some(GeneStatistics {
+            diversity: Diversity::new,
+            changed_genes: |a, b| a.iter().zip(b.iter()).filter(|(a, b)| a != b).count(),
+        });
+        self
+    }
+}
+
+// Everything needed to turn two parents into a child, shareable between
+// threads
+struct Operators<'a, G> {
+    crossover_method: &'a dyn CrossoverMethod<G>,
+    mutation_method: &'a dyn MutationMethod<G>,
+    repair: Option<&'a Repair<G>>,
+    statistics: Option<&'a GeneStatistics<G>>,
+    local_search: Option<&'a LocalSearch<G>>,
+}
+
+struct Offspring<G> {
+    // Refined if there's local search
+    chromosome: Chromosome<G>,
+    // The unrefined genes, under Baldwinian local search
+    genotype: Option<Chromosome<G>>,
+    // 0 without gene statistics
+    mutated_genes: usize,
+}
+
+impl<G: Clone> Operators<'_, G> {
+    // Crossover, mutation, repair and local search
+    fn breed(&self, rng: &mut dyn RngCore, parent_a: &Chromosome<G>, parent_b: &Chromosome<G>) -> Offspring<G> {
+        //crossover
+        let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+        child.inherit_from(parent_a, parent_b);
+        //mutation
+        let crossed = self.statistics.map(|_| child.clone());
+        self.mutation_method.mutate(rng, &mut child);
+        let mutated = match (self.statistics, crossed) {
+            (Some(statistics), Some(crossed)) => (statistics.changed_genes)(&crossed, &child),
+            _ => 0,
+        };
+
+        if let Some(repair) = self.repair {
+            repair(&mut child);
+        }
+
+        let Some(local_search) = self.local_search else {
+            return Offspring {
+                chromosome: child,
+                genotype: None,
+                mutated_genes: mutated,
+            };
+        };
+        let (refined, _) = local_search.refine(rng, &child, self.repair);
+        Offspring {
+            chromosome: refined,
+            genotype: (local_search.inheritance() == Inheritance::Baldwinian).then_some(child),
+            mutated_genes: mutated,
+        }
+    }
+}
+
+// Indices sorted from highest to lowest fitness
+pub(crate) fn best_first(fitness: &[f32]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..fitness.len()).collect();
+    ranked.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    ranked
+}
+
+// The `count` individuals with the highest fitness, best first
+pub(crate) fn fittest<I: Individual<G>, G>(population: &[I], count: usize) -> impl Iterator<Item = &I> {
+    let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness()).collect();
+    best_first(&fitness)
+        .into_iter()
+        .take(count)
+        .map(move |index| &population[index])
+}
+
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub struct TestIndividual {
+    fitness: f32,
+    chromosome: Chromosome,
+}
+
+#[cfg(test)]
+impl TestIndividual {
+    pub fn new(fitness: f32) -> Self {
+        Self {
+            fitness,
+            chromosome: Chromosome::from_iter([]),
+        }
+    }
+
+    pub fn with_chromosome(fitness: f32, chromosome: Chromosome) -> Self {
+        Self {
+            fitness,
+            chromosome,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Individual for TestIndividual {
+    fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    fn chromosome(&self) -> &Chromosome {
+        &self.chromosome
+    }
+
+    fn create(chromosome: Chromosome) -> Self {
+        Self {
+            fitness: 0.0,
+            chromosome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossover_method::UniformCrossover;
+    use mutation_method::GaussianMutation;
+    use selection_method::RouletteWheelSelection;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::sync::{Arc, Mutex};
+
+    fn individual(fitness: f32, genes: &[f32]) -> TestIndividual {
+        TestIndividual::with_chromosome(fitness, genes.iter().copied().collect())
+    }
+
+    #[test]
+    fn elites_survive_unchanged() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            individual(1.0, &[0.0, 0.0, 0.0]),
+            individual(5.0, &[1.0, 2.0, 3.0]),
+            individual(2.0, &[4.0, 4.0, 4.0]),
+            individual(4.0, &[0.5, 0.5, 0.5]),
+        ];
+
+        // Every gene of every non-elite child gets mutated
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(1.0, 0.5),
+        )
+        .with_elitism(2)
+        .with_gene_statistics();
+
+        let (children, report) = ga.evolve(&mut rng, &population).unwrap();
+
+        assert_eq!(children.len(), population.len());
+        assert_eq!(children[0].chromosome(), population[1].chromosome());
+        assert_eq!(children[1].chromosome(), population[3].chromosome());
+        assert!(children[2..]
+            .iter()
+            .all(|child| population.iter().all(|parent| parent.chromosome() != child.chromosome())));
+
+        assert_eq!(report.fitness.max, 5.0);
+        assert_eq!(report.mutated_genes, 2 * 3);
+        assert_eq!(report.parent_usage.iter().sum::<usize>(), 2 * 2);
+    }
+
+    #[test]
+    fn elites_are_picked_on_own_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        // The best individual shares a species with three others, the loner
+        // doesn't share at all
+        let population = vec![
+            individual(9.0, &[0.1]),
+            individual(9.0, &[0.1]),
+            individual(10.0, &[0.0]),
+            individual(9.0, &[0.1]),
+            individual(5.0, &[100.0]),
+        ];
+        let shared = speciation::Speciation::new(1.0).shared_fitness(&population);
+        assert!(shared[2] < shared[4]);
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_elitism(1);
+
+        let (children, _) = ga.evolve_with_fitness(&mut rng, &population, &shared).unwrap();
+
+        assert_eq!(children[0].chromosome(), population[2].chromosome());
+    }
+
+    // Fitness is the number of set bits
+    struct OneMax(Chromosome<bool>);
+
+    impl Individual<bool> for OneMax {
+        fn fitness(&self) -> f32 {
+            self.0.iter().filter(|&&bit| bit).count() as f32
+        }
+
+        fn chromosome(&self) -> &Chromosome<bool> {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome<bool>) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    #[test]
+    fn evolves_bitstrings() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population: Vec<OneMax> = (0..40)
+            .map(|_| OneMax((0..32).map(|_| rng.gen_bool(0.2)).collect()))
+            .collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            selection_method::TournamentSelection::new(3),
+            UniformCrossover,
+            mutation_method::BitFlipMutation::new(0.02),
+        )
+        .with_elitism(1);
+
+        let best = |population: &[OneMax]| population.iter().map(|individual| individual.fitness()).fold(0.0, f32::max);
+        let initial = best(&population);
+
+        for _ in 0..30 {
+            population = ga.evolve(&mut rng, &population).unwrap().0;
+        }
+
+        assert!(best(&population) > initial + 10.0);
+    }
+
+    // Genes without a numeric value, e.g. a route through named cities
+    struct Route(Chromosome<&'static str>);
+
+    impl Individual<&'static str> for Route {
+        fn fitness(&self) -> f32 {
+            (self.0[0] == "home") as usize as f32
+        }
+
+        fn chromosome(&self) -> &Chromosome<&'static str> {
+            &self.0
+        }
+
+        fn create(chromosome: Chromosome<&'static str>) -> Self {
+            Self(chromosome)
+        }
+    }
+
+    #[test]
+    fn evolves_non_numeric_genes() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            Route(["home", "work", "gym"].into_iter().collect()),
+            Route(["work", "gym", "home"].into_iter().collect()),
+        ];
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            mutation_method::SwapMutation::new(0.5),
+        );
+        let (children, report) = ga.evolve(&mut rng, &population).unwrap();
+
+        assert_eq!(children.len(), 2);
+        assert_eq!(report.mutated_genes, 0);
+        assert_eq!(report.diversity, Default::default());
+    }
+
+    struct Recorder {
+        name: &'static str,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EvolutionObserver for Recorder {
+        fn on_generation_start(&mut self, generation: usize, _fitness: &[f32]) {
+            self.events.lock().unwrap().push(format!("{} start {}", self.name, generation));
+        }
+
+        fn on_selection(&mut self, _generation: usize, parents: &[usize]) {
+            self.events.lock().unwrap().push(format!("{} selected {}", self.name, parents.len()));
+        }
+
+        fn on_generation_end(&mut self, generation: usize, _report: &EvolutionReport) {
+            self.events.lock().unwrap().push(format!("{} end {}", self.name, generation));
+        }
+    }
+
+    #[test]
+    fn observers_are_notified_in_order() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let population = vec![individual(1.0, &[0.0]), individual(2.0, &[1.0]), individual(3.0, &[2.0])];
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+        )
+        .with_observer(Recorder { name: "a", events: events.clone() })
+        .with_observer(Recorder { name: "b", events: events.clone() });
+
+        ga.evolve(&mut rng, &population).unwrap();
+        ga.evolve(&mut rng, &population).unwrap();
+
+        assert_eq!(ga.generation(), 2);
+        assert_eq!(events.lock().unwrap()[..6], [
+            "a start 0", "b start 0", "a selected 6", "b selected 6", "a end 0", "b end 0",
+        ]);
+        assert_eq!(events.lock().unwrap()[11], "b end 1");
+    }
+
+    #[test]
+    fn steady_state_replaces_only_the_worst() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..6).map(|i| individual(i as f32 + 1.0, &[i as f32])).collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(1.0, 0.5),
+        )
+        .with_replacement(replacement::SteadyState::new(2));
+
+        let (children, report) = ga.evolve(&mut rng, &population).unwrap();
+
+        let kept: Vec<f32> = children[..4].iter().map(|child| child.chromosome()[0]).collect();
+        assert_eq!(kept, vec![5.0, 4.0, 3.0, 2.0]);
+        assert_eq!(children.len(), 6);
+        assert_eq!(report.parent_usage.iter().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn comma_breeds_only_from_the_best() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..6).map(|i| individual(i as f32 + 1.0, &[i as f32])).collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_replacement(replacement::MuCommaLambda::new(2));
+
+        let (children, report) = ga.evolve(&mut rng, &population).unwrap();
+
+        assert_eq!(children.len(), 6);
+        assert!(children.iter().all(|child| child.chromosome()[0] >= 4.0));
+        assert_eq!(report.parent_usage[4] + report.parent_usage[5], 12);
+    }
+
+    #[test]
+    fn parallel_evolve_ignores_thread_count() {
+        let population: Vec<_> = (0..50)
+            .map(|i| individual(i as f32 + 1.0, &[i as f32; 20]))
+            .collect();
+
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut ga = GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    GaussianMutation::new(0.5, 0.5),
+                )
+                .with_gene_statistics();
+                let (children, report) = ga.evolve_parallel(&mut rng, &population).unwrap();
+                let chromosomes: Vec<Chromosome> = children.into_iter().map(|child| child.chromosome().clone()).collect();
+                (chromosomes, report)
+            })
+        };
+
+        let (single, single_report) = run(1);
+        for threads in [2, 3, 8] {
+            let (multi, multi_report) = run(threads);
+            assert!(single.iter().zip(&multi).all(|(a, b)| {
+                a.iter().zip(b.iter()).all(|(x, y)| x.to_bits() == y.to_bits())
+            }));
+            assert_eq!(single_report, multi_report);
+        }
+        assert!(single_report.mutated_genes > 0);
+    }
+
+    #[test]
+    fn children_are_repaired_into_bounds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..10).map(|i| individual(1.0 + i as f32, &[0.9; 50])).collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(1.0, 2.0),
+        )
+        .with_repair(bounds::BoundsRepair::new(bounds::RepairMethod::Reflect).with_default_bounds(-1.0..=1.0));
+
+        let (children, _) = ga.evolve(&mut rng, &population).unwrap();
+
+        assert!(children.iter().flat_map(|child| child.chromosome().iter()).all(|gene| (-1.0..=1.0).contains(gene)));
+    }
+
+    #[test]
+    fn penalty_steers_selection() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        // Equally fit, but the second breaks the constraint `gene <= 0`
+        let population = vec![individual(10.0, &[0.0]), individual(10.0, &[1.0])];
+
+        let mut ga = GeneticAlgorithm::new(
+            selection_method::TournamentSelection::new(2),
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.0),
+        )
+        .with_penalty(|chromosome| chromosome[0].max(0.0) * 100.0);
+
+        let (_, report) = ga.evolve(&mut rng, &population).unwrap();
+
+        assert!(report.parent_usage[0] > report.parent_usage[1]);
+    }
+
+    #[test]
+    fn local_search_refines_children() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![individual(0.0, &[0.0]), individual(-1.0, &[-1.0])];
+        let evaluated = Arc::new(Mutex::new(Vec::new()));
+
+        for inheritance in [memetic::Inheritance::Lamarckian, memetic::Inheritance::Baldwinian] {
+            let log = Arc::clone(&evaluated);
+            let local_search = memetic::LocalSearch::new(
+                memetic::LocalSearchMethod::HillClimbing { step: 0.25 },
+                4,
+                inheritance,
+                move |chromosome: &Chromosome| {
+                    log.lock().unwrap().push(chromosome[0]);
+                    chromosome[0]
+                },
+            );
+            let mut ga = GeneticAlgorithm::new(
+                selection_method::TournamentSelection::new(2),
+                UniformCrossover,
+                GaussianMutation::new(0.0, 0.0),
+            )
+            .with_local_search(local_search);
+
+            // Without mutation, children are refined copies of their parents
+            let (children, _) = ga.evolve(&mut rng, &population).unwrap();
+            assert!(children.iter().any(|child| ![0.0, -1.0].contains(&child.chromosome()[0])));
+
+            let children: Vec<_> = children
+                .iter()
+                .map(|child| individual(child.chromosome()[0], child.chromosome().as_slice()))
+                .collect();
+            evaluated.lock().unwrap().clear();
+            ga.evolve(&mut rng, &children).unwrap();
+
+            // Each refinement starts by scoring the unrefined child
+            let unrefined: Vec<f32> = evaluated.lock().unwrap().chunks(5).map(|scores| scores[0]).collect();
+            let bred_from_originals = unrefined.iter().all(|gene| [0.0, -1.0].contains(gene));
+            assert_eq!(bred_from_originals, inheritance == memetic::Inheritance::Baldwinian);
+        }
+    }
+
+    #[test]
+    fn schedules_set_parameters_each_generation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..10).map(|i| individual(1.0 + i as f32, &[0.0; 5])).collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            selection_method::TournamentSelection::new(2),
+            UniformCrossover,
+            GaussianMutation::new(0.0, 0.5),
+        )
+        .with_schedule(schedule::Parameter::MutationChance, schedule::LinearDecay::new(1.0, 0.0, 2))
+        .with_schedule(schedule::Parameter::SelectionPressure, schedule::StepDecay::new(4.0, 0.5, 1))
+        .with_gene_statistics();
+
+        let (children, report) = ga.evolve(&mut rng, &population).unwrap();
+        assert_eq!(report.parameters, vec![
+            (schedule::Parameter::MutationChance, 1.0),
+            (schedule::Parameter::SelectionPressure, 4.0),
+        ]);
+        assert_eq!(report.mutated_genes, 10 * 5);
+        assert_eq!(report.success_rate, None);
+
+        // Every child scores 0, so none beat their parents
+        let (children, report) = ga.evolve(&mut rng, &children).unwrap();
+        assert_eq!(report.parameters[0].1, 0.5);
+        assert_eq!(report.success_rate, Some(0.0));
+
+        ga.forget_lineage();
+        let (_, report) = ga.evolve(&mut rng, &children).unwrap();
+        assert_eq!(report.success_rate, None);
+    }
+
+    #[test]
+    fn schedules_skip_missing_parameters() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..4).map(|i| individual(1.0 + i as f32, &[0.0])).collect();
+
+        let mut ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            mutation_method::SwapMutation::new(0.1),
+        )
+        .with_schedule(schedule::Parameter::SelectionPressure, schedule::LinearDecay::new(2.0, 1.0, 10))
+        .with_schedule(schedule::Parameter::MutationChance, schedule::LinearDecay::new(1.0, 0.0, 10));
+
+        let (_, report) = ga.evolve(&mut rng, &population).unwrap();
+        assert!(report.parameters.is_empty());
+    }
+}
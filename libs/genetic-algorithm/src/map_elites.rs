@@ -0,0 +1,266 @@
+use crate::*;
+
+/// One dimension of the behavior grid: `min..max` split into `bins` equal
+/// cells. Behaviors outside the range land in the nearest edge cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BehaviorAxis {
+    min: f32,
+    max: f32,
+    bins: usize,
+}
+
+impl BehaviorAxis {
+    pub fn new(min: f32, max: f32, bins: usize) -> Self {
+        assert!(min < max);
+        assert!(bins > 0);
+        Self {
+            min,
+            max,
+            bins,
+        }
+    }
+
+    pub fn bin(&self, value: f32) -> usize {
+        let scaled = (value - self.min) / (self.max - self.min) * self.bins as f32;
+        (scaled.max(0.0) as usize).min(self.bins - 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Elite<G = f32> {
+    cell: Vec<usize>,
+    behavior: Vec<f32>,
+    fitness: f32,
+    chromosome: Chromosome<G>,
+}
+
+impl<G> Elite<G> {
+    // Bin index along each axis
+    pub fn cell(&self) -> &[usize] {
+        &self.cell
+    }
+
+    pub fn behavior(&self) -> &[f32] {
+        &self.behavior
+    }
+
+    pub fn fitness(&self) -> f32 {
+        self.fitness
+    }
+
+    pub fn chromosome(&self) -> &Chromosome<G> {
+        &self.chromosome
+    }
+}
+
+/// MAP-Elites (Mouret and Clune): keeps the fittest chromosome found for
+/// every cell of a behavior grid, and breeds new candidates from random
+/// elites rather than from one converging population.
+pub struct MapElites<G = f32> {
+    axes: Vec<BehaviorAxis>,
+    // Row-major over `axes`, first axis slowest
+    cells: Vec<Option<Elite<G>>>,
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+}
+
+impl<G: Gene> MapElites<G> {
+    pub fn new(
+        axes: Vec<BehaviorAxis>,
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
+    ) -> Self {
+        assert!(!axes.is_empty());
+        let num_cells = axes.iter().map(|axis| axis.bins).product();
+        Self {
+            axes,
+            cells: vec![None; num_cells],
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+        }
+    }
+
+    pub fn axes(&self) -> &[BehaviorAxis] {
+        &self.axes
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Fraction of cells holding an elite
+    pub fn coverage(&self) -> f32 {
+        self.len() as f32 / self.num_cells() as f32
+    }
+
+    // Filled cells in grid order
+    pub fn elites(&self) -> impl Iterator<Item = &Elite<G>> {
+        self.cells.iter().flatten()
+    }
+
+    // Snapshot of every filled cell, e.g. for plotting or saving
+    pub fn export(&self) -> Vec<Elite<G>> {
+        self.elites().cloned().collect()
+    }
+
+    pub fn cell_of(&self, behavior: &[f32]) -> Vec<usize> {
+        assert_eq!(behavior.len(), self.axes.len());
+        self.axes
+            .iter()
+            .zip(behavior)
+            .map(|(axis, &value)| axis.bin(value))
+            .collect()
+    }
+
+    pub fn get(&self, cell: &[usize]) -> Option<&Elite<G>> {
+        self.cells[self.index(cell)].as_ref()
+    }
+
+    // Keeps the chromosome if its cell is empty or holds a less fit elite
+    pub fn insert(&mut self, chromosome: Chromosome<G>, fitness: f32, behavior: Vec<f32>) -> bool {
+        if !fitness.is_finite() {
+            return false;
+        }
+
+        let cell = self.cell_of(&behavior);
+        let index = self.index(&cell);
+
+        if self.cells[index].as_ref().is_some_and(|elite| elite.fitness >= fitness) {
+            return false;
+        }
+
+        self.cells[index] = Some(Elite {
+            cell,
+            behavior,
+            fitness,
+            chromosome,
+        });
+        true
+    }
+
+    // Offers every evaluated individual to the archive, returning how many
+    // made it in
+    pub fn insert_population<I: Individual<G>>(&mut self, population: &[I], behaviors: &[Vec<f32>]) -> usize {
+        assert_eq!(population.len(), behaviors.len());
+        population
+            .iter()
+            .zip(behaviors)
+            .filter(|(individual, behavior)| {
+                self.insert(individual.chromosome().clone(), individual.fitness(), behavior.to_vec())
+            })
+            .count()
+    }
+
+    // Breeds `count` new chromosomes from uniformly chosen elites
+    pub fn sample(&self, rng: &mut dyn RngCore, count: usize) -> Vec<Chromosome<G>> {
+        let elites: Vec<&Elite<G>> = self.elites().collect();
+        assert!(!elites.is_empty(), "MAP-Elites archive is empty");
+
+        (0..count)
+            .map(|_| {
+                let parent_a = &elites.choose(rng).unwrap().chromosome;
+                let parent_b = &elites.choose(rng).unwrap().chromosome;
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                child.inherit_from(parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+                child
+            })
+            .collect()
+    }
+
+    // Archives the evaluated population, then breeds a new one of equal size
+    pub fn evolve<I: Individual<G>>(&mut self, rng: &mut dyn RngCore, population: &[I], behaviors: &[Vec<f32>]) -> Vec<I> {
+        self.insert_population(population, behaviors);
+        self.sample(rng, population.len())
+            .into_iter()
+            .map(I::create)
+            .collect()
+    }
+
+    fn index(&self, cell: &[usize]) -> usize {
+        assert_eq!(cell.len(), self.axes.len());
+        cell.iter()
+            .zip(&self.axes)
+            .fold(0, |index, (&bin, axis)| {
+                assert!(bin < axis.bins);
+                index * axis.bins + bin
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossover_method::UniformCrossover;
+    use mutation_method::GaussianMutation;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn archive() -> MapElites {
+        MapElites::new(
+            vec![BehaviorAxis::new(0.0, 1.0, 4), BehaviorAxis::new(-1.0, 1.0, 2)],
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.1),
+        )
+    }
+
+    #[test]
+    fn bins_clamp_to_edges() {
+        let axis = BehaviorAxis::new(0.0, 1.0, 4);
+        assert_eq!(axis.bin(-3.0), 0);
+        assert_eq!(axis.bin(0.3), 1);
+        assert_eq!(axis.bin(0.99), 3);
+        assert_eq!(axis.bin(1.0), 3);
+    }
+
+    #[test]
+    fn keeps_the_fittest_per_cell() {
+        let mut archive = archive();
+        let chromosome = |gene: f32| -> Chromosome { [gene].into_iter().collect() };
+
+        assert!(archive.insert(chromosome(1.0), 1.0, vec![0.1, 0.5]));
+        assert!(archive.insert(chromosome(2.0), 3.0, vec![0.2, 0.9]));
+        assert!(!archive.insert(chromosome(3.0), 2.0, vec![0.0, 0.6]));
+        assert!(archive.insert(chromosome(4.0), 0.5, vec![0.9, -0.5]));
+
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.coverage(), 0.25);
+        assert_eq!(archive.get(&[0, 1]).unwrap().chromosome()[0], 2.0);
+
+        let cells: Vec<&[usize]> = archive.elites().map(Elite::cell).collect();
+        assert_eq!(cells, vec![&[0, 1][..], &[3, 0][..]]);
+        assert_eq!(archive.export().len(), 2);
+    }
+
+    #[test]
+    fn fills_the_grid() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut archive = archive();
+
+        // Behavior is the genes themselves, fitness prefers small ones
+        let evaluate = |chromosomes: Vec<Chromosome>| -> (Vec<TestIndividual>, Vec<Vec<f32>>) {
+            let behaviors = chromosomes.iter().map(|c| c.as_slice().to_vec()).collect();
+            let population = chromosomes
+                .into_iter()
+                .map(|c| TestIndividual::with_chromosome(-c.iter().map(|g| g.abs()).sum::<f32>(), c))
+                .collect();
+            (population, behaviors)
+        };
+
+        let (mut population, mut behaviors) = evaluate(vec![[0.5, 0.0].into_iter().collect(); 10]);
+        for _ in 0..50 {
+            let children = archive.evolve(&mut rng, &population, &behaviors);
+            (population, behaviors) = evaluate(children.into_iter().map(|c| c.chromosome().clone()).collect());
+        }
+
+        assert_eq!(archive.coverage(), 1.0);
+    }
+}
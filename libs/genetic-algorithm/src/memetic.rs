@@ -0,0 +1,144 @@
+use crate::*;
+use rand_distr::StandardNormal;
+
+type Evaluate<G> = dyn Fn(&Chromosome<G>) -> f32 + Send + Sync;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LocalSearchMethod {
+    // Nudges one random gene up or down by `step`, keeping improvements
+    HillClimbing { step: f32 },
+    // Adds gaussian noise to every gene, keeping improvements
+    RandomPerturbation { sigma: f32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inheritance {
+    // Refined genes replace the originals
+    Lamarckian,
+    // The refined genes get evaluated, but children inherit the originals
+    Baldwinian,
+}
+
+/// Short local refinement of each child after breeding, turning the genetic
+/// algorithm into a memetic one. `evaluate` scores a candidate, typically by
+/// running a brief episode, and is called `iterations + 1` times per child.
+/// Its scores are only compared with each other, so they needn't match the
+/// scale of the fitness `evolve` selects on.
+pub struct LocalSearch<G = f32> {
+    method: LocalSearchMethod,
+    iterations: usize,
+    inheritance: Inheritance,
+    evaluate: Box<Evaluate<G>>,
+    // `perturb` for this gene type, so refining doesn't need `G: Gene`
+    perturb: fn(LocalSearchMethod, &mut dyn RngCore, &mut Chromosome<G>),
+}
+
+impl<G: Gene> LocalSearch<G> {
+    pub fn new(
+        method: LocalSearchMethod,
+        iterations: usize,
+        inheritance: Inheritance,
+        evaluate: impl Fn(&Chromosome<G>) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            method,
+            iterations,
+            inheritance,
+            evaluate: Box::new(evaluate),
+            perturb,
+        }
+    }
+}
+
+impl<G: Clone> LocalSearch<G> {
+    pub fn inheritance(&self) -> Inheritance {
+        self.inheritance
+    }
+
+    // Best chromosome found starting from `chromosome`, along with its
+    // score. Candidates are repaired before evaluation.
+    pub fn refine(
+        &self,
+        rng: &mut dyn RngCore,
+        chromosome: &Chromosome<G>,
+        repair: Option<&Repair<G>>,
+    ) -> (Chromosome<G>, f32) {
+        let mut best = (chromosome.clone(), (self.evaluate)(chromosome));
+        if chromosome.is_empty() {
+            return best;
+        }
+
+        for _ in 0..self.iterations {
+            let mut candidate = best.0.clone();
+            (self.perturb)(self.method, rng, &mut candidate);
+            if let Some(repair) = repair {
+                repair(&mut candidate);
+            }
+
+            let candidate_fitness = (self.evaluate)(&candidate);
+            if candidate_fitness > best.1 {
+                best = (candidate, candidate_fitness);
+            }
+        }
+
+        best
+    }
+}
+
+fn perturb<G: Gene>(method: LocalSearchMethod, rng: &mut dyn RngCore, chromosome: &mut Chromosome<G>) {
+    match method {
+        LocalSearchMethod::HillClimbing { step } => {
+            let index = rng.gen_range(0..chromosome.len());
+            let sign = if rng.gen_bool(0.5) {1.0} else {-1.0};
+            let gene = &mut chromosome.as_mut_slice()[index];
+            *gene = G::from_value(gene.value() + sign * step);
+        }
+        LocalSearchMethod::RandomPerturbation { sigma } => {
+            for gene in chromosome.iter_mut() {
+                let noise: f32 = rng.sample(StandardNormal);
+                *gene = G::from_value(gene.value() + sigma * noise);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // Peaks at every gene being 1
+    fn peak(chromosome: &Chromosome) -> f32 {
+        -chromosome.iter().map(|gene| (gene - 1.0).powi(2)).sum::<f32>()
+    }
+
+    #[test]
+    fn refinement_never_gets_worse() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let chromosome: Chromosome = [0.0, 0.0, 0.0].into_iter().collect();
+
+        for method in [
+            LocalSearchMethod::HillClimbing { step: 0.1 },
+            LocalSearchMethod::RandomPerturbation { sigma: 0.1 },
+        ] {
+            let search = LocalSearch::new(method, 50, Inheritance::Lamarckian, peak);
+            let (refined, fitness) = search.refine(&mut rng, &chromosome, None);
+
+            assert_eq!(fitness, peak(&refined));
+            assert!(fitness > peak(&chromosome));
+        }
+    }
+
+    #[test]
+    fn refinement_respects_repair() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let chromosome: Chromosome = [0.0, 0.0].into_iter().collect();
+        let repair = BoundsRepair::new(bounds::RepairMethod::Clamp).with_default_bounds(-0.5..=0.5);
+
+        let search = LocalSearch::new(LocalSearchMethod::HillClimbing { step: 0.3 }, 100, Inheritance::Lamarckian, peak);
+        let (refined, _) = search.refine(&mut rng, &chromosome, Some(&move |candidate: &mut Chromosome| repair.apply(candidate)));
+
+        assert_eq!(refined.as_slice(), &[0.5, 0.5]);
+    }
+}
@@ -1,43 +1,450 @@
 use crate::*;
 use lib_config::Config;
-pub trait MutationMethod {
-    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
+use rand::distributions::uniform::SampleUniform;
+use rand_distr::StandardNormal;
+use std::ops::RangeInclusive;
+
+pub use lib_config::MutationKind;
+
+// Send + Sync so `evolve_parallel` can share one across threads
+pub trait MutationMethod<G = f32>: Send + Sync {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>);
+
+    // Multiplies mutation chance and strength until set back to 1.0, e.g. to
+    // shake a stalled run loose. Methods without either ignore it.
+    fn set_boost(&mut self, _boost: f32) {}
+
+    // Overwrite the per-gene mutation chance or strength, e.g. from a
+    // `Schedule`. Methods without one return false.
+    fn set_chance(&mut self, _chance: f32) -> bool {
+        false
+    }
+
+    fn set_coeff(&mut self, _coeff: f32) -> bool {
+        false
+    }
 }
 
+// Keeps self-adaptive step sizes from collapsing to zero
+const MIN_STEP_SIZE: f32 = 1e-5;
+
 #[derive(Debug)]
 pub struct GaussianMutation {
     chance: f32,
     coeff: f32,
+    kind: MutationKind,
+    boost: f32,
 }
 
 #[allow(unused)]
 impl GaussianMutation {
     pub fn new(chance: f32, coeff: f32) -> Self {
-        assert!(chance >= 0.0 && chance <= 1.0);
+        Self::with_kind(chance, coeff, MutationKind::Gaussian)
+    }
+
+    pub fn polynomial(chance: f32, coeff: f32, eta: f32) -> Self {
+        Self::with_kind(chance, coeff, MutationKind::Polynomial { eta })
+    }
+
+    pub fn self_adaptive(chance: f32, initial_step_size: f32) -> Self {
+        Self::with_kind(chance, initial_step_size, MutationKind::SelfAdaptive)
+    }
+
+    pub fn with_kind(chance: f32, coeff: f32, kind: MutationKind) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        if let MutationKind::Polynomial { eta } = kind {
+            assert!(eta.is_finite() && eta >= 0.0, "Polynomial mutation needs a finite, non-negative eta");
+        }
         Self {
             chance,
             coeff,
+            kind,
+            boost: 1.0,
         }
     }
 
     pub fn from_config(config: Config) -> Self {
-        assert!(config.mutation_chance >= 0.0 && config.mutation_chance <= 1.0);
-        assert!(config.mutation_coef >= 0.0 && config.mutation_coef <= 1.0);
-        Self {
-            chance: config.mutation_chance,
-            coeff: config.mutation_coef,
+        assert!((0.0..=1.0).contains(&config.mutation_coef));
+        Self::with_kind(config.mutation_chance, config.mutation_coef, config.mutation_kind)
+    }
+
+    fn chance(&self) -> f64 {
+        (self.chance * self.boost).min(1.0) as f64
+    }
+
+    fn coeff(&self) -> f32 {
+        self.coeff * self.boost
+    }
+
+    fn polynomial_delta(rng: &mut dyn RngCore, eta: f32) -> f32 {
+        let u: f32 = rng.gen();
+        if u < 0.5 {
+            (2.0 * u).powf(1.0 / (eta + 1.0)) - 1.0
+        } else {
+            1.0 - (2.0 * (1.0 - u)).powf(1.0 / (eta + 1.0))
         }
     }
+
+    // Log-normal step size update from Schwefel's (1, lambda)-ES
+    fn mutate_self_adaptive(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        let len = child.len() as f32;
+        let global_rate = 1.0 / (2.0 * len).sqrt();
+        let local_rate = 1.0 / (2.0 * len.sqrt()).sqrt();
+
+        let mut step_sizes = child.step_sizes().to_vec();
+        if step_sizes.len() != child.len() {
+            step_sizes = vec![self.coeff; child.len()];
+        }
+
+        let global: f32 = rng.sample(StandardNormal);
+
+        for (gene, step_size) in child.iter_mut().zip(step_sizes.iter_mut()) {
+            if rng.gen_bool(self.chance()) {
+                let local: f32 = rng.sample(StandardNormal);
+                *step_size = (*step_size * (global_rate * global + local_rate * local).exp()).max(MIN_STEP_SIZE);
+                *gene += *step_size * rng.sample::<f32, _>(StandardNormal);
+            }
+        }
+
+        child.set_step_sizes(step_sizes);
+    }
 }
 
 impl MutationMethod for GaussianMutation {
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        match self.kind {
+            MutationKind::Gaussian => {
+                for gene in child.iter_mut() {
+                    if rng.gen_bool(self.chance()) {
+                        *gene += self.coeff() * rng.sample::<f32, _>(StandardNormal);
+                    }
+                }
+            }
+            MutationKind::Polynomial { eta } => {
+                for gene in child.iter_mut() {
+                    if rng.gen_bool(self.chance()) {
+                        *gene += self.coeff() * Self::polynomial_delta(rng, eta);
+                    }
+                }
+            }
+            MutationKind::SelfAdaptive => self.mutate_self_adaptive(rng, child),
+        }
+    }
+
+    // Self-adaptive step sizes evolve on their own, so only the chance is
+    // boosted for them
+    fn set_boost(&mut self, boost: f32) {
+        assert!(boost > 0.0);
+        self.boost = boost;
+    }
+
+    fn set_chance(&mut self, chance: f32) -> bool {
+        self.chance = chance.clamp(0.0, 1.0);
+        true
+    }
+
+    // Also the starting step size of self-adaptive mutation
+    fn set_coeff(&mut self, coeff: f32) -> bool {
+        self.coeff = coeff.max(0.0);
+        true
+    }
+}
+
+/// Flips each bit of a bitstring with probability `chance`.
+#[derive(Debug)]
+pub struct BitFlipMutation {
+    chance: f32,
+}
+
+impl BitFlipMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self {
+            chance,
+        }
+    }
+}
+
+impl MutationMethod<bool> for BitFlipMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<bool>) {
         for gene in child.iter_mut() {
-            let sign: f32 = if rng.gen_bool(0.5) {-1.0} else {1.0};
+            if rng.gen_bool(self.chance as f64) {
+                *gene = !*gene;
+            }
+        }
+    }
+
+    fn set_chance(&mut self, chance: f32) -> bool {
+        self.chance = chance.clamp(0.0, 1.0);
+        true
+    }
+}
 
+/// Replaces genes with a fresh uniform draw from `bounds`. Works for any
+/// gene type `rand` can sample, e.g. bounded integers.
+#[derive(Debug)]
+pub struct RandomResetMutation<G> {
+    chance: f32,
+    bounds: RangeInclusive<G>,
+}
+
+impl<G: PartialOrd> RandomResetMutation<G> {
+    pub fn new(chance: f32, bounds: RangeInclusive<G>) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(bounds.start() <= bounds.end());
+        Self {
+            chance,
+            bounds,
+        }
+    }
+}
+
+impl<G> MutationMethod<G> for RandomResetMutation<G>
+where
+    G: SampleUniform + PartialOrd + Clone + Send + Sync,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        for gene in child.iter_mut() {
             if rng.gen_bool(self.chance as f64) {
-                *gene += sign * self.coeff * rng.gen::<f32>();
+                *gene = rng.gen_range(self.bounds.clone());
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Nudges integer genes up or down by at most `step`, staying within `bounds`.
+#[derive(Debug)]
+pub struct CreepMutation {
+    chance: f32,
+    step: i32,
+    bounds: RangeInclusive<i32>,
+}
+
+impl CreepMutation {
+    pub fn new(chance: f32, step: i32, bounds: RangeInclusive<i32>) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        assert!(step > 0);
+        assert!(bounds.start() <= bounds.end());
+        Self {
+            chance,
+            step,
+            bounds,
+        }
+    }
+}
+
+impl MutationMethod<i32> for CreepMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<i32>) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.chance as f64) {
+                let delta = rng.gen_range(-self.step..=self.step);
+                *gene = gene.saturating_add(delta).clamp(*self.bounds.start(), *self.bounds.end());
+            }
+        }
+    }
+}
+
+/// Swaps each gene with another random position with probability `chance`.
+/// Keeps permutations valid.
+#[derive(Debug)]
+pub struct SwapMutation {
+    chance: f32,
+}
+
+impl SwapMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self {
+            chance,
+        }
+    }
+}
+
+impl<G> MutationMethod<G> for SwapMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        let len = child.len();
+        for i in 0..len {
+            if rng.gen_bool(self.chance as f64) {
+                let j = rng.gen_range(0..len);
+                child.as_mut_slice().swap(i, j);
+            }
+        }
+    }
+}
+
+/// With probability `chance`, reverses a random slice of the chromosome.
+/// Keeps permutations valid and only breaks two adjacencies.
+#[derive(Debug)]
+pub struct InversionMutation {
+    chance: f32,
+}
+
+impl InversionMutation {
+    pub fn new(chance: f32) -> Self {
+        assert!((0.0..=1.0).contains(&chance));
+        Self {
+            chance,
+        }
+    }
+}
+
+impl<G> MutationMethod<G> for InversionMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome<G>) {
+        let len = child.len();
+        if len < 2 || !rng.gen_bool(self.chance as f64) {
+            return;
+        }
+
+        let a = rng.gen_range(0..len);
+        let b = rng.gen_range(0..len);
+        child.as_mut_slice()[a.min(b)..=a.max(b)].reverse();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn zeros() -> Chromosome {
+        vec![0.0; 5000].into_iter().collect()
+    }
+
+    #[test]
+    fn gaussian_is_normally_distributed() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child = zeros();
+
+        GaussianMutation::new(1.0, 0.5).mutate(&mut rng, &mut child);
+
+        let len = child.len() as f32;
+        let mean = child.iter().sum::<f32>() / len;
+        let std_dev = (child.iter().map(|gene| (gene - mean).powi(2)).sum::<f32>() / len).sqrt();
+
+        assert_relative_eq!(mean, 0.0, epsilon = 0.05);
+        assert_relative_eq!(std_dev, 0.5, epsilon = 0.05);
+        // A uniform perturbation could never reach past the coefficient
+        assert!(child.iter().any(|gene| gene.abs() > 1.0));
+    }
+
+    #[test]
+    fn zero_chance_leaves_child_untouched() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        for mutation in [
+            GaussianMutation::new(0.0, 0.5),
+            GaussianMutation::polynomial(0.0, 0.5, 20.0),
+            GaussianMutation::self_adaptive(0.0, 0.5),
+        ] {
+            let mut child = zeros();
+            mutation.mutate(&mut rng, &mut child);
+            assert!(child.iter().all(|&gene| gene == 0.0));
+        }
+    }
+
+    #[test]
+    fn polynomial_is_bounded_by_coeff() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child = zeros();
+
+        GaussianMutation::polynomial(1.0, 0.5, 20.0).mutate(&mut rng, &mut child);
+
+        assert!(child.iter().all(|gene| gene.abs() <= 0.5));
+        assert!(child.iter().any(|&gene| gene != 0.0));
+    }
+
+    #[test]
+    fn boost_scales_chance_and_strength() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut mutation = GaussianMutation::polynomial(0.1, 0.5, 20.0);
+        let mutated = |child: &Chromosome| child.iter().filter(|&&gene| gene != 0.0).count() as f32 / 5000.0;
+
+        mutation.set_boost(4.0);
+        let mut child = zeros();
+        mutation.mutate(&mut rng, &mut child);
+        assert_relative_eq!(mutated(&child), 0.4, epsilon = 0.03);
+        assert!(child.iter().any(|gene| gene.abs() > 0.5));
+
+        mutation.set_boost(1.0);
+        let mut child = zeros();
+        mutation.mutate(&mut rng, &mut child);
+        assert_relative_eq!(mutated(&child), 0.1, epsilon = 0.03);
+        assert!(child.iter().all(|gene| gene.abs() <= 0.5));
+    }
+
+    #[test]
+    fn self_adaptive_evolves_step_sizes() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child = zeros();
+        let mutation = GaussianMutation::self_adaptive(1.0, 0.1);
+
+        mutation.mutate(&mut rng, &mut child);
+        let first = child.step_sizes().to_vec();
+        mutation.mutate(&mut rng, &mut child);
+
+        assert_eq!(first.len(), child.len());
+        assert!(first.iter().all(|&step_size| step_size != 0.1));
+        assert_ne!(first, child.step_sizes());
+    }
+
+    #[test]
+    fn bit_flip_flips_about_chance_bits() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<bool> = vec![false; 1000].into_iter().collect();
+
+        BitFlipMutation::new(0.2).mutate(&mut rng, &mut child);
+
+        let flipped = child.iter().filter(|&&bit| bit).count();
+        assert!((150..250).contains(&flipped));
+    }
+
+    #[test]
+    fn integer_mutations_respect_bounds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<i32> = vec![0; 1000].into_iter().collect();
+
+        for _ in 0..20 {
+            CreepMutation::new(0.5, 3, -5..=5).mutate(&mut rng, &mut child);
+            assert!(child.iter().all(|gene| (-5..=5).contains(gene)));
+        }
+
+        RandomResetMutation::new(1.0, 10..=12).mutate(&mut rng, &mut child);
+        assert!(child.iter().all(|gene| (10..=12).contains(gene)));
+    }
+
+    #[test]
+    fn permutation_mutations_keep_permutations() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut child: Chromosome<usize> = (0..50).collect();
+
+        for _ in 0..50 {
+            SwapMutation::new(0.1).mutate(&mut rng, &mut child);
+            InversionMutation::new(0.5).mutate(&mut rng, &mut child);
+        }
+
+        let mut genes = child.as_slice().to_vec();
+        assert_ne!(genes, (0..50).collect::<Vec<_>>());
+        genes.sort_unstable();
+        assert_eq!(genes, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn from_config_picks_kind() {
+        let mut config = Config::new(9, 1, 18, 0.25, 0.01, 0.3);
+        assert_eq!(GaussianMutation::from_config(config).kind, MutationKind::Gaussian);
+
+        config.mutation_kind = MutationKind::Polynomial { eta: 15.0 };
+        assert_eq!(GaussianMutation::from_config(config).kind, MutationKind::Polynomial { eta: 15.0 });
+    }
+
+    #[test]
+    fn from_config_rejects_bad_eta() {
+        for eta in [-1.0, f32::NAN, f32::INFINITY] {
+            let mut config = Config::new(9, 1, 18, 0.25, 0.01, 0.3);
+            config.mutation_kind = MutationKind::Polynomial { eta };
+            assert!(std::panic::catch_unwind(|| GaussianMutation::from_config(config)).is_err());
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// Novelty search archive, after Lehman and Stanley. An individual's novelty
+/// is its mean distance to the `k` nearest behaviors among the archive and
+/// the rest of its generation; sufficiently novel behaviors get archived.
+#[derive(Clone, Debug)]
+pub struct NoveltyArchive {
+    k: usize,
+    // Novelty needed to enter the archive
+    threshold: f32,
+    // Oldest behaviors are dropped first once full
+    capacity: usize,
+    behaviors: Vec<Vec<f32>>,
+}
+
+impl NoveltyArchive {
+    pub fn new(k: usize, threshold: f32, capacity: usize) -> Self {
+        assert!(k > 0);
+        assert!(threshold >= 0.0);
+        assert!(capacity > 0);
+        Self {
+            k,
+            threshold,
+            capacity,
+            behaviors: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    pub fn behaviors(&self) -> &[Vec<f32>] {
+        &self.behaviors
+    }
+
+    // Novelty of every behavior in a generation, archiving the novel ones
+    // afterwards so a generation isn't compared against itself twice
+    pub fn evaluate(&mut self, behaviors: &[Vec<f32>]) -> Vec<f32> {
+        let scores: Vec<f32> = (0..behaviors.len())
+            .map(|index| self.novelty(&behaviors[index], behaviors, index))
+            .collect();
+
+        for (behavior, &score) in behaviors.iter().zip(&scores) {
+            if score >= self.threshold {
+                self.behaviors.push(behavior.clone());
+            }
+        }
+        if self.behaviors.len() > self.capacity {
+            let excess = self.behaviors.len() - self.capacity;
+            self.behaviors.drain(..excess);
+        }
+
+        scores
+    }
+
+    fn novelty(&self, behavior: &[f32], generation: &[Vec<f32>], own_index: usize) -> f32 {
+        let mut distances: Vec<f32> = generation
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != own_index)
+            .map(|(_, other)| other)
+            .chain(&self.behaviors)
+            .map(|other| distance(behavior, other))
+            .collect();
+
+        if distances.is_empty() {
+            return 0.0;
+        }
+
+        let k = self.k.min(distances.len());
+        distances.select_nth_unstable_by(k - 1, f32::total_cmp);
+        distances[..k].iter().sum::<f32>() / k as f32
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt()
+}
+
+/// `(1 - weight) * fitness + weight * novelty`, with both rescaled to 0..=1
+/// across the population first so neither dominates by sheer magnitude.
+pub fn blend(fitness: &[f32], novelty: &[f32], weight: f32) -> Vec<f32> {
+    assert_eq!(fitness.len(), novelty.len());
+    assert!((0.0..=1.0).contains(&weight));
+
+    let fitness = normalized(fitness);
+    let novelty = normalized(novelty);
+
+    fitness
+        .iter()
+        .zip(&novelty)
+        .map(|(f, n)| (1.0 - weight) * f + weight * n)
+        .collect()
+}
+
+fn normalized(values: &[f32]) -> Vec<f32> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    if max > min {
+        values.iter().map(|value| (value - min) / (max - min)).collect()
+    } else {
+        vec![0.0; values.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn outliers_are_most_novel() {
+        let mut archive = NoveltyArchive::new(2, 1.0, 10);
+        let behaviors = vec![vec![0.0, 0.0], vec![0.1, 0.0], vec![0.0, 0.1], vec![5.0, 5.0]];
+
+        let scores = archive.evaluate(&behaviors);
+
+        assert_eq!(scores.iter().copied().fold(0.0, f32::max), scores[3]);
+        assert_relative_eq!(scores[1], 0.5 * (0.1 + 2f32.sqrt() * 0.1));
+        // Only the outlier clears the threshold
+        assert_eq!(archive.behaviors(), &[vec![5.0, 5.0]]);
+    }
+
+    #[test]
+    fn archived_behaviors_stop_being_novel() {
+        let mut archive = NoveltyArchive::new(1, 0.0, 2);
+        let first = archive.evaluate(&[vec![0.0], vec![3.0]]);
+        let second = archive.evaluate(&[vec![0.0], vec![10.0]]);
+
+        assert_eq!(first, vec![3.0, 3.0]);
+        // `0.0` was seen last generation, `10.0` is still far from everything
+        assert_eq!(second, vec![0.0, 7.0]);
+        assert_eq!(archive.len(), 2);
+    }
+
+    #[test]
+    fn blend_weights_normalized_scores() {
+        let blended = blend(&[0.0, 10.0, 5.0], &[2.0, 0.0, 1.0], 0.25);
+        assert_eq!(blended, vec![0.25, 0.75, 0.5]);
+
+        assert_eq!(blend(&[1.0, 2.0], &[7.0, 7.0], 1.0), vec![0.0, 0.0]);
+    }
+}
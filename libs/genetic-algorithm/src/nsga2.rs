@@ -0,0 +1,289 @@
+use crate::*;
+
+/// An individual scored on several objectives at once, all maximised.
+pub trait MultiObjectiveIndividual<G = f32>: Individual<G> {
+    fn objectives(&self) -> Vec<f32>;
+}
+
+// `a` is at least as good everywhere and strictly better somewhere
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    assert_eq!(a.len(), b.len());
+
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+// Fast non-dominated sort from Deb et al.; returns indices grouped into
+// fronts, the Pareto front first
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+    let mut dominated_by = vec![0; len];
+    let mut dominating: Vec<Vec<usize>> = vec![Vec::new(); len];
+
+    for p in 0..len {
+        for q in 0..len {
+            if dominates(&objectives[p], &objectives[q]) {
+                dominating[p].push(q);
+            } else if dominates(&objectives[q], &objectives[p]) {
+                dominated_by[p] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = (0..len).filter(|&p| dominated_by[p] == 0).collect();
+
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &p in &current {
+            for &q in &dominating[p] {
+                dominated_by[q] -= 1;
+                if dominated_by[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+        fronts.push(current);
+        current = next;
+    }
+
+    fronts
+}
+
+// Crowding distance of every member of `front`, in the same order.
+// Boundary points get infinity so they are always preferred.
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distance = vec![0.0; front.len()];
+    if front.len() <= 2 {
+        return vec![f32::INFINITY; front.len()];
+    }
+
+    let num_objectives = objectives[front[0]].len();
+    for m in 0..num_objectives {
+        let mut sorted: Vec<usize> = (0..front.len()).collect();
+        sorted.sort_by(|&a, &b| objectives[front[a]][m].total_cmp(&objectives[front[b]][m]));
+
+        let min = objectives[front[sorted[0]]][m];
+        let max = objectives[front[sorted[sorted.len() - 1]]][m];
+
+        distance[sorted[0]] = f32::INFINITY;
+        distance[sorted[sorted.len() - 1]] = f32::INFINITY;
+
+        if max == min {
+            continue;
+        }
+
+        for i in 1..sorted.len() - 1 {
+            let gap = objectives[front[sorted[i + 1]]][m] - objectives[front[sorted[i - 1]]][m];
+            distance[sorted[i]] += gap / (max - min);
+        }
+    }
+
+    distance
+}
+
+// Non-dominated front rank and crowding distance for every individual
+fn rank_and_crowding(objectives: &[Vec<f32>]) -> (Vec<usize>, Vec<f32>) {
+    let mut rank = vec![0; objectives.len()];
+    let mut crowding = vec![0.0; objectives.len()];
+
+    for (front_rank, front) in non_dominated_sort(objectives).into_iter().enumerate() {
+        for (&index, distance) in front.iter().zip(crowding_distance(objectives, &front)) {
+            rank[index] = front_rank;
+            crowding[index] = distance;
+        }
+    }
+
+    (rank, crowding)
+}
+
+pub fn pareto_front<I: MultiObjectiveIndividual<G>, G>(population: &[I]) -> Vec<&I> {
+    let objectives: Vec<_> = population.iter().map(|individual| individual.objectives()).collect();
+
+    non_dominated_sort(&objectives)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|index| &population[index])
+        .collect()
+}
+
+pub struct Nsga2<G = f32> {
+    crossover_method: Box<dyn CrossoverMethod<G>>,
+    mutation_method: Box<dyn MutationMethod<G>>,
+}
+
+impl<G> Nsga2<G> {
+    pub fn new(
+        crossover_method: impl CrossoverMethod<G> + 'static,
+        mutation_method: impl MutationMethod<G> + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+        }
+    }
+
+    // Breeds a new generation, picking parents by binary tournament on
+    // (front rank, crowding distance)
+    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Result<Vec<I>, SelectionError>
+    where
+        I: MultiObjectiveIndividual<G>,
+    {
+        if population.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        let objectives: Vec<_> = population.iter().map(|individual| individual.objectives()).collect();
+        let (rank, crowding) = rank_and_crowding(&objectives);
+
+        let tournament = |rng: &mut dyn RngCore| {
+            let a = rng.gen_range(0..population.len());
+            let b = rng.gen_range(0..population.len());
+            let a_wins = rank[a] < rank[b] || (rank[a] == rank[b] && crowding[a] >= crowding[b]);
+            if a_wins {a} else {b}
+        };
+
+        let children = (0..population.len())
+            .map(|_| {
+                let parent_a = population[tournament(rng)].chromosome();
+                let parent_b = population[tournament(rng)].chromosome();
+
+                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+                child.inherit_from(parent_a, parent_b);
+                self.mutation_method.mutate(rng, &mut child);
+
+                I::create(child)
+            })
+            .collect();
+
+        Ok(children)
+    }
+
+    // Environmental selection: keep the best `count` of an evaluated
+    // parents + offspring pool, front by front, breaking the last tie on
+    // crowding distance
+    pub fn survivors<'a, I>(&self, pool: &'a [I], count: usize) -> Vec<&'a I>
+    where
+        I: MultiObjectiveIndividual<G>,
+    {
+        let objectives: Vec<_> = pool.iter().map(|individual| individual.objectives()).collect();
+        let mut survivors = Vec::with_capacity(count);
+
+        for mut front in non_dominated_sort(&objectives) {
+            if survivors.len() + front.len() > count {
+                let crowding = crowding_distance(&objectives, &front);
+                let mut order: Vec<usize> = (0..front.len()).collect();
+                order.sort_by(|&a, &b| crowding[b].total_cmp(&crowding[a]));
+                front = order.into_iter().map(|i| front[i]).collect();
+            }
+
+            for index in front {
+                if survivors.len() == count {
+                    return survivors;
+                }
+                survivors.push(&pool[index]);
+            }
+        }
+
+        survivors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossover_method::UniformCrossover;
+    use mutation_method::GaussianMutation;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    struct TestMultiIndividual {
+        objectives: Vec<f32>,
+        chromosome: Chromosome,
+    }
+
+    impl Individual for TestMultiIndividual {
+        fn fitness(&self) -> f32 {
+            self.objectives.iter().sum()
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+
+        fn create(chromosome: Chromosome) -> Self {
+            Self {
+                objectives: Vec::new(),
+                chromosome,
+            }
+        }
+    }
+
+    impl MultiObjectiveIndividual for TestMultiIndividual {
+        fn objectives(&self) -> Vec<f32> {
+            self.objectives.clone()
+        }
+    }
+
+    fn individual(a: f32, b: f32) -> TestMultiIndividual {
+        TestMultiIndividual {
+            objectives: vec![a, b],
+            chromosome: [a, b].into_iter().collect(),
+        }
+    }
+
+    fn objectives() -> Vec<Vec<f32>> {
+        vec![
+            vec![1.0, 5.0],
+            vec![2.0, 2.0],
+            vec![5.0, 1.0],
+            vec![1.0, 1.0],
+            vec![3.0, 3.0],
+        ]
+    }
+
+    #[test]
+    fn sorts_into_fronts() {
+        let fronts = non_dominated_sort(&objectives());
+
+        assert_eq!(fronts, vec![vec![0, 2, 4], vec![1], vec![3]]);
+    }
+
+    #[test]
+    fn crowding_prefers_boundaries() {
+        let objectives = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![3.0, 1.0],
+            vec![4.0, 0.0],
+        ];
+
+        let distance = crowding_distance(&objectives, &[0, 1, 2, 3]);
+
+        assert_eq!(distance[0], f32::INFINITY);
+        assert_eq!(distance[3], f32::INFINITY);
+        assert_eq!(distance[1], distance[2]);
+        assert!(distance[1].is_finite());
+    }
+
+    #[test]
+    fn survivors_fill_front_by_front() {
+        let pool: Vec<_> = objectives().into_iter().map(|o| individual(o[0], o[1])).collect();
+        let nsga2 = Nsga2::new(UniformCrossover, GaussianMutation::new(0.1, 0.1));
+
+        let survivors: Vec<_> = nsga2.survivors(&pool, 4).into_iter().map(|i| i.objectives()).collect();
+
+        assert_eq!(survivors, vec![vec![1.0, 5.0], vec![5.0, 1.0], vec![3.0, 3.0], vec![2.0, 2.0]]);
+        assert_eq!(pareto_front(&pool).len(), 3);
+    }
+
+    #[test]
+    fn evolve_keeps_population_size() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let pool: Vec<_> = objectives().into_iter().map(|o| individual(o[0], o[1])).collect();
+        let nsga2 = Nsga2::new(UniformCrossover, GaussianMutation::new(0.1, 0.1));
+
+        assert_eq!(nsga2.evolve(&mut rng, &pool).unwrap().len(), pool.len());
+    }
+}
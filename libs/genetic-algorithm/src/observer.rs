@@ -0,0 +1,13 @@
+use crate::evolution_report::EvolutionReport;
+
+/// Hooks into `GeneticAlgorithm::evolve`, e.g. for logging or checkpoints.
+/// Every method defaults to doing nothing.
+pub trait EvolutionObserver: Send {
+    // `fitness` is what selection will see, after any sharing or scaling
+    fn on_generation_start(&mut self, _generation: usize, _fitness: &[f32]) {}
+
+    // Indices of the chosen parents, consecutive pairs breed together
+    fn on_selection(&mut self, _generation: usize, _parents: &[usize]) {}
+
+    fn on_generation_end(&mut self, _generation: usize, _report: &EvolutionReport) {}
+}
@@ -0,0 +1,136 @@
+use crate::*;
+
+/// Decides how much of an evaluated population makes it into the next
+/// generation. `evolve` copies the survivors, then breeds children from the
+/// parent pool until the population is back to its original size.
+pub trait ReplacementStrategy: Send {
+    // Indices carried over unchanged
+    fn survivors(&self, fitness: &[f32]) -> Vec<usize>;
+
+    // Indices allowed to breed
+    fn parents(&self, fitness: &[f32]) -> Vec<usize>;
+}
+
+/// Every individual may breed and every one is replaced by a child.
+#[derive(Debug)]
+pub struct Generational;
+
+impl ReplacementStrategy for Generational {
+    fn survivors(&self, _fitness: &[f32]) -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn parents(&self, fitness: &[f32]) -> Vec<usize> {
+        (0..fitness.len()).collect()
+    }
+}
+
+/// Only the `count` worst individuals are replaced each generation.
+#[derive(Debug)]
+pub struct SteadyState {
+    count: usize,
+}
+
+impl SteadyState {
+    pub fn new(count: usize) -> Self {
+        assert!(count > 0);
+        Self {
+            count,
+        }
+    }
+}
+
+impl ReplacementStrategy for SteadyState {
+    fn survivors(&self, fitness: &[f32]) -> Vec<usize> {
+        let mut survivors = best_first(fitness);
+        survivors.truncate(fitness.len().saturating_sub(self.count));
+        survivors
+    }
+
+    fn parents(&self, fitness: &[f32]) -> Vec<usize> {
+        (0..fitness.len()).collect()
+    }
+}
+
+/// (μ+λ) evolution strategy: the `mu` best breed and survive alongside their
+/// children, so λ is the population size minus `mu`.
+#[derive(Debug)]
+pub struct MuPlusLambda {
+    mu: usize,
+}
+
+impl MuPlusLambda {
+    pub fn new(mu: usize) -> Self {
+        assert!(mu > 0);
+        Self {
+            mu,
+        }
+    }
+}
+
+impl ReplacementStrategy for MuPlusLambda {
+    fn survivors(&self, fitness: &[f32]) -> Vec<usize> {
+        self.parents(fitness)
+    }
+
+    fn parents(&self, fitness: &[f32]) -> Vec<usize> {
+        let mut parents = best_first(fitness);
+        parents.truncate(self.mu);
+        parents
+    }
+}
+
+/// (μ,λ) evolution strategy: the `mu` best breed, then the whole population
+/// is replaced by λ children, λ being the population size.
+#[derive(Debug)]
+pub struct MuCommaLambda {
+    mu: usize,
+}
+
+impl MuCommaLambda {
+    pub fn new(mu: usize) -> Self {
+        assert!(mu > 0);
+        Self {
+            mu,
+        }
+    }
+}
+
+impl ReplacementStrategy for MuCommaLambda {
+    fn survivors(&self, _fitness: &[f32]) -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn parents(&self, fitness: &[f32]) -> Vec<usize> {
+        let mut parents = best_first(fitness);
+        parents.truncate(self.mu);
+        parents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FITNESS: [f32; 5] = [3.0, 1.0, 5.0, 2.0, 4.0];
+
+    fn sorted(mut indices: Vec<usize>) -> Vec<usize> {
+        indices.sort_unstable();
+        indices
+    }
+
+    #[test]
+    fn survivors_and_parents() {
+        assert!(Generational.survivors(&FITNESS).is_empty());
+        assert_eq!(Generational.parents(&FITNESS), vec![0, 1, 2, 3, 4]);
+
+        assert_eq!(sorted(SteadyState::new(2).survivors(&FITNESS)), vec![0, 2, 4]);
+        assert_eq!(SteadyState::new(2).parents(&FITNESS).len(), 5);
+
+        assert_eq!(sorted(MuPlusLambda::new(2).survivors(&FITNESS)), vec![2, 4]);
+        assert_eq!(sorted(MuPlusLambda::new(2).parents(&FITNESS)), vec![2, 4]);
+
+        assert!(MuCommaLambda::new(2).survivors(&FITNESS).is_empty());
+        assert_eq!(sorted(MuCommaLambda::new(2).parents(&FITNESS)), vec![2, 4]);
+    }
+}
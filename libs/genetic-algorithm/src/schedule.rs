@@ -0,0 +1,192 @@
+use std::f32::consts::PI;
+use std::ops::RangeInclusive;
+
+/// What a schedule adjusts each generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    // See `MutationMethod::set_chance`
+    MutationChance,
+    // See `MutationMethod::set_coeff`
+    MutationCoeff,
+    // See `SelectionMethod::set_pressure`
+    SelectionPressure,
+}
+
+pub trait Schedule: Send {
+    // Value for `generation`. `success_rate` is the fraction of the previous
+    // generation's children that beat the fitter of their parents, when the
+    // genetic algorithm could tell.
+    fn value(&mut self, generation: usize, success_rate: Option<f32>) -> f32;
+}
+
+/// Moves in a straight line from `start` to `end` over `generations`, then
+/// holds `end`.
+#[derive(Debug)]
+pub struct LinearDecay {
+    start: f32,
+    end: f32,
+    generations: usize,
+}
+
+impl LinearDecay {
+    pub fn new(start: f32, end: f32, generations: usize) -> Self {
+        assert!(generations > 0);
+        Self {
+            start,
+            end,
+            generations,
+        }
+    }
+}
+
+impl Schedule for LinearDecay {
+    fn value(&mut self, generation: usize, _success_rate: Option<f32>) -> f32 {
+        let progress = (generation as f32 / self.generations as f32).min(1.0);
+        self.start + (self.end - self.start) * progress
+    }
+}
+
+/// `start * rate^generation`, never dropping below `min`.
+#[derive(Debug)]
+pub struct ExponentialDecay {
+    start: f32,
+    rate: f32,
+    min: f32,
+}
+
+impl ExponentialDecay {
+    pub fn new(start: f32, rate: f32, min: f32) -> Self {
+        assert!(rate > 0.0);
+        Self {
+            start,
+            rate,
+            min,
+        }
+    }
+}
+
+impl Schedule for ExponentialDecay {
+    fn value(&mut self, generation: usize, _success_rate: Option<f32>) -> f32 {
+        (self.start * self.rate.powi(generation as i32)).max(self.min)
+    }
+}
+
+/// Follows half a cosine wave from `start` to `end` over `generations`, then
+/// holds `end`. Changes slowly at both ends and fastest in the middle.
+#[derive(Debug)]
+pub struct CosineAnnealing {
+    start: f32,
+    end: f32,
+    generations: usize,
+}
+
+impl CosineAnnealing {
+    pub fn new(start: f32, end: f32, generations: usize) -> Self {
+        assert!(generations > 0);
+        Self {
+            start,
+            end,
+            generations,
+        }
+    }
+}
+
+impl Schedule for CosineAnnealing {
+    fn value(&mut self, generation: usize, _success_rate: Option<f32>) -> f32 {
+        let progress = (generation as f32 / self.generations as f32).min(1.0);
+        self.end + (self.start - self.end) * (1.0 + (PI * progress).cos()) / 2.0
+    }
+}
+
+/// Multiplies `start` by `factor` once every `every` generations.
+#[derive(Debug)]
+pub struct StepDecay {
+    start: f32,
+    factor: f32,
+    every: usize,
+}
+
+impl StepDecay {
+    pub fn new(start: f32, factor: f32, every: usize) -> Self {
+        assert!(every > 0);
+        Self {
+            start,
+            factor,
+            every,
+        }
+    }
+}
+
+impl Schedule for StepDecay {
+    fn value(&mut self, generation: usize, _success_rate: Option<f32>) -> f32 {
+        self.start * self.factor.powi((generation / self.every) as i32)
+    }
+}
+
+/// Rechenberg's 1/5th success rule: grows the value by `factor` while more
+/// than a fifth of the children beat their parents, and shrinks it while
+/// fewer do. Holds still until the success rate is known.
+#[derive(Debug)]
+pub struct OneFifthRule {
+    value: f32,
+    factor: f32,
+    bounds: RangeInclusive<f32>,
+}
+
+impl OneFifthRule {
+    pub fn new(initial: f32, factor: f32, bounds: RangeInclusive<f32>) -> Self {
+        assert!(factor > 1.0);
+        assert!(bounds.contains(&initial));
+        Self {
+            value: initial,
+            factor,
+            bounds,
+        }
+    }
+}
+
+impl Schedule for OneFifthRule {
+    fn value(&mut self, _generation: usize, success_rate: Option<f32>) -> f32 {
+        match success_rate {
+            Some(rate) if rate > 0.2 => self.value *= self.factor,
+            Some(rate) if rate < 0.2 => self.value /= self.factor,
+            _ => {}
+        }
+        self.value = self.value.clamp(*self.bounds.start(), *self.bounds.end());
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn values(schedule: &mut dyn Schedule, generations: &[usize]) -> Vec<f32> {
+        generations.iter().map(|&generation| schedule.value(generation, None)).collect()
+    }
+
+    #[test]
+    fn time_based_schedules() {
+        assert_eq!(values(&mut LinearDecay::new(1.0, 0.0, 4), &[0, 1, 4, 10]), vec![1.0, 0.75, 0.0, 0.0]);
+        assert_eq!(values(&mut ExponentialDecay::new(1.0, 0.5, 0.2), &[0, 1, 2, 3]), vec![1.0, 0.5, 0.25, 0.2]);
+        assert_eq!(values(&mut StepDecay::new(1.0, 0.5, 2), &[0, 1, 2, 5]), vec![1.0, 1.0, 0.5, 0.25]);
+
+        let cosine = values(&mut CosineAnnealing::new(1.0, 0.0, 4), &[0, 2, 4, 8]);
+        for (value, expected) in cosine.into_iter().zip([1.0, 0.5, 0.0, 0.0]) {
+            assert_relative_eq!(value, expected, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn one_fifth_rule_follows_success() {
+        let mut rule = OneFifthRule::new(1.0, 2.0, 0.25..=4.0);
+
+        assert_eq!(rule.value(0, None), 1.0);
+        assert_eq!(rule.value(1, Some(0.5)), 2.0);
+        assert_eq!(rule.value(2, Some(0.5)), 4.0);
+        assert_eq!(rule.value(3, Some(0.5)), 4.0);
+        assert_eq!(rule.value(4, Some(0.2)), 4.0);
+        assert_eq!(rule.value(5, Some(0.0)), 2.0);
+    }
+}
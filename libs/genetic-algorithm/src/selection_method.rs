@@ -1,21 +1,483 @@
 use crate::*;
+use crate::fitness_transform::FitnessTransform;
+use rand::distributions::{Distribution, WeightedError, WeightedIndex};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionError {
+    EmptyPopulation,
+    // NaN, infinite, or negative where the method needs weights
+    InvalidFitness,
+    // Every weight is zero, so there is nothing to be proportional to
+    AllFitnessZero,
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyPopulation => write!(f, "cannot select from an empty population"),
+            Self::InvalidFitness => write!(f, "fitness values must be finite and non-negative"),
+            Self::AllFitnessZero => write!(f, "every individual has zero fitness"),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+impl From<WeightedError> for SelectionError {
+    fn from(err: WeightedError) -> Self {
+        match err {
+            WeightedError::NoItem => Self::EmptyPopulation,
+            WeightedError::AllWeightsZero => Self::AllFitnessZero,
+            WeightedError::InvalidWeight | WeightedError::TooMany => Self::InvalidFitness,
+        }
+    }
+}
 
 pub trait SelectionMethod {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError>;
+
+    // Methods that pick all parents in one go (e.g. SUS) override this
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, SelectionError> {
+        (0..count)
+            .map(|_| self.select_index(rng, fitness))
+            .collect()
+    }
+
+    fn select<'a, I, G>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<&'a I, SelectionError>
+    where
+        I: Individual<G>,
+    {
+        let index = self.select_index(rng, &fitness_of(population))?;
+        Ok(&population[index])
+    }
+
+    fn select_many<'a, I, G>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Result<Vec<&'a I>, SelectionError>
     where
-        I: Individual;
+        I: Individual<G>,
+    {
+        let indices = self.select_indices(rng, &fitness_of(population), count)?;
+        Ok(indices.into_iter().map(|index| &population[index]).collect())
+    }
+
+    // Adjust how strongly the fittest are favoured, e.g. from a `Schedule`.
+    // What `pressure` means depends on the method; ones without a knob
+    // return false.
+    fn set_pressure(&mut self, _pressure: f32) -> bool {
+        false
+    }
+}
+
+fn fitness_of<I: Individual<G>, G>(population: &[I]) -> Vec<f32> {
+    population.iter().map(|individual| individual.fitness()).collect()
 }
 
 // Selection
 pub struct RouletteWheelSelection;
 
 impl SelectionMethod for RouletteWheelSelection {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
-    where
-        I: Individual,
-    {
-        population
-            .choose_weighted(rng, |individual| individual.fitness())
-            .expect("Empty population")
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError> {
+        Ok(WeightedIndex::new(fitness)?.sample(rng))
+    }
+
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, SelectionError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let wheel = WeightedIndex::new(fitness)?;
+        Ok((0..count).map(|_| wheel.sample(rng)).collect())
+    }
+}
+
+/// Picks `size` random individuals and returns the fittest of them.
+#[derive(Debug)]
+pub struct TournamentSelection {
+    size: usize,
+}
+
+impl TournamentSelection {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0);
+        Self {
+            size,
+        }
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError> {
+        if fitness.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+
+        let winner = (0..self.size)
+            .map(|_| rng.gen_range(0..fitness.len()))
+            .max_by(|&a, &b| fitness[a].total_cmp(&fitness[b]))
+            .unwrap();
+
+        Ok(winner)
+    }
+
+    // Tournament size, rounded
+    fn set_pressure(&mut self, pressure: f32) -> bool {
+        self.size = (pressure.round() as usize).max(1);
+        true
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RankScaling {
+    // Best individual is `pressure` times as likely as the average one, 1.0..=2.0
+    Linear { pressure: f32 },
+    // Each step down the ranking multiplies the weight by `base`, 0.0..1.0
+    Exponential { base: f32 },
+}
+
+/// Selects proportionally to position in the fitness ranking rather than to
+/// raw fitness, so a handful of outliers can't dominate the next generation.
+#[derive(Debug)]
+pub struct RankSelection {
+    scaling: RankScaling,
+}
+
+impl RankSelection {
+    pub fn linear(pressure: f32) -> Self {
+        assert!((1.0..=2.0).contains(&pressure));
+        Self {
+            scaling: RankScaling::Linear { pressure },
+        }
+    }
+
+    pub fn exponential(base: f32) -> Self {
+        assert!(base > 0.0 && base < 1.0);
+        Self {
+            scaling: RankScaling::Exponential { base },
+        }
+    }
+
+    // Weight of the individual at `rank`, where 0 is the worst
+    fn weight(&self, rank: usize, len: usize) -> f32 {
+        match self.scaling {
+            RankScaling::Linear { pressure } => {
+                if len == 1 {
+                    1.0
+                } else {
+                    (2.0 - pressure) + 2.0 * (pressure - 1.0) * rank as f32 / (len - 1) as f32
+                }
+            }
+            RankScaling::Exponential { base } => base.powi((len - 1 - rank) as i32),
+        }
+    }
+}
+
+impl SelectionMethod for RankSelection {
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError> {
+        Ok(self.select_indices(rng, fitness, 1)?[0])
+    }
+
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, SelectionError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut ranked: Vec<usize> = (0..fitness.len()).collect();
+        ranked.sort_by(|&a, &b| fitness[a].total_cmp(&fitness[b]));
+
+        let wheel = WeightedIndex::new((0..ranked.len()).map(|rank| self.weight(rank, ranked.len())))?;
+
+        Ok((0..count).map(|_| ranked[wheel.sample(rng)]).collect())
+    }
+
+    // Linear pressure, or the exponential base, clamped to its valid range
+    fn set_pressure(&mut self, pressure: f32) -> bool {
+        self.scaling = match self.scaling {
+            RankScaling::Linear { .. } => RankScaling::Linear { pressure: pressure.clamp(1.0, 2.0) },
+            RankScaling::Exponential { .. } => RankScaling::Exponential { base: pressure.clamp(f32::EPSILON, 1.0 - f32::EPSILON) },
+        };
+        true
+    }
+}
+
+/// Roulette wheel with `count` equally spaced pointers, spun once. Gives the
+/// same expected counts as `RouletteWheelSelection` with far less spread.
+#[derive(Debug)]
+pub struct StochasticUniversalSampling;
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError> {
+        Ok(self.select_indices(rng, fitness, 1)?[0])
+    }
+
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, SelectionError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        if fitness.is_empty() {
+            return Err(SelectionError::EmptyPopulation);
+        }
+        if fitness.iter().any(|&f| !f.is_finite() || f < 0.0) {
+            return Err(SelectionError::InvalidFitness);
+        }
+
+        let total: f32 = fitness.iter().sum();
+        // Finite weights can still add up to infinity
+        if !total.is_finite() {
+            return Err(SelectionError::InvalidFitness);
+        }
+        if total <= 0.0 {
+            return Err(SelectionError::AllFitnessZero);
+        }
+
+        let spacing = total / count as f32;
+        let start = rng.gen_range(0.0..spacing);
+
+        let mut selected = Vec::with_capacity(count);
+        let mut current = 0;
+        let mut cumulative = fitness[0];
+
+        for i in 0..count {
+            let pointer = start + i as f32 * spacing;
+
+            // Pointers on a boundary belong to the next slot, so zero-weight
+            // slots are never picked. Rounding can leave the last pointer
+            // just past the end.
+            while cumulative <= pointer && current + 1 < fitness.len() {
+                current += 1;
+                cumulative += fitness[current];
+            }
+
+            selected.push(current);
+        }
+
+        // Pointers walk the population in order, so adjacent picks would
+        // otherwise always be paired with their neighbours
+        selected.shuffle(rng);
+        Ok(selected)
     }
-}
\ No newline at end of file
+}
+
+/// Runs fitness through a `FitnessTransform` before handing it to the wrapped
+/// selection method, e.g. `Scaled::new(RouletteWheelSelection, UniformFallback)`.
+#[derive(Debug)]
+pub struct Scaled<S, T> {
+    selection: S,
+    transform: T,
+}
+
+impl<S, T> Scaled<S, T>
+where
+    S: SelectionMethod,
+    T: FitnessTransform,
+{
+    pub fn new(selection: S, transform: T) -> Self {
+        Self {
+            selection,
+            transform,
+        }
+    }
+}
+
+impl<S, T> SelectionMethod for Scaled<S, T>
+where
+    S: SelectionMethod,
+    T: FitnessTransform,
+{
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, SelectionError> {
+        self.selection.select_index(rng, &self.transform.transform(fitness))
+    }
+
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, SelectionError> {
+        self.selection.select_indices(rng, &self.transform.transform(fitness), count)
+    }
+
+    fn set_pressure(&mut self, pressure: f32) -> bool {
+        self.selection.set_pressure(pressure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitness_transform::UniformFallback;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeMap;
+
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            TestIndividual::new(2.0),
+            TestIndividual::new(1.0),
+            TestIndividual::new(4.0),
+            TestIndividual::new(3.0),
+        ]
+    }
+
+    fn histogram(selected: Vec<&TestIndividual>) -> BTreeMap<i32, usize> {
+        selected
+            .into_iter()
+            .fold(BTreeMap::new(), |mut histogram, individual| {
+                *histogram.entry(individual.fitness() as i32).or_insert(0) += 1;
+                histogram
+            })
+    }
+
+    #[test]
+    fn roulette_wheel_favours_fitter_individuals() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        let histogram = histogram(RouletteWheelSelection.select_many(&mut rng, &population, 1000).unwrap());
+
+        assert!(histogram[&1] < histogram[&2]);
+        assert!(histogram[&2] < histogram[&3]);
+        assert!(histogram[&3] < histogram[&4]);
+    }
+
+    #[test]
+    fn tournament_of_one_is_uniform() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        let histogram = histogram(TournamentSelection::new(1).select_many(&mut rng, &population, 1000).unwrap());
+
+        for count in histogram.values() {
+            assert!((200..=300).contains(count), "{:?}", histogram);
+        }
+    }
+
+    #[test]
+    fn tournament_as_large_as_population_mostly_picks_best() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        let histogram = histogram(TournamentSelection::new(8).select_many(&mut rng, &population, 1000).unwrap());
+
+        assert!(histogram[&4] > 850, "{:?}", histogram);
+    }
+
+    #[test]
+    fn linear_rank_ignores_fitness_magnitude() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![
+            TestIndividual::new(1.0),
+            TestIndividual::new(2.0),
+            TestIndividual::new(1000.0),
+        ];
+
+        // Pressure 2.0 gives rank weights 0, 1 and 2
+        let histogram = histogram(RankSelection::linear(2.0).select_many(&mut rng, &population, 3000).unwrap());
+
+        assert_eq!(histogram.get(&1), None);
+        assert!((900..=1100).contains(&histogram[&2]), "{:?}", histogram);
+        assert!((1900..=2100).contains(&histogram[&1000]), "{:?}", histogram);
+    }
+
+    #[test]
+    fn exponential_rank_decays_by_base() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        let histogram = histogram(RankSelection::exponential(0.5).select_many(&mut rng, &population, 1500).unwrap());
+
+        // Expected 800 / 400 / 200 / 100
+        assert!(histogram[&4] > histogram[&3]);
+        assert!(histogram[&3] > histogram[&2]);
+        assert!(histogram[&2] > histogram[&1]);
+    }
+
+    #[test]
+    fn sus_matches_expected_counts() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        // Fitness sums to 10, so 10 pointers land exactly once per fitness unit
+        let histogram = histogram(StochasticUniversalSampling.select_many(&mut rng, &population, 10).unwrap());
+
+        assert_eq!(histogram, BTreeMap::from([(1, 1), (2, 2), (3, 3), (4, 4)]));
+        assert!(StochasticUniversalSampling.select_many(&mut rng, &population, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn zero_fitness_is_an_error_not_a_panic() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![TestIndividual::new(0.0); 4];
+
+        assert_eq!(
+            RouletteWheelSelection.select(&mut rng, &population).unwrap_err(),
+            SelectionError::AllFitnessZero,
+        );
+        assert_eq!(
+            StochasticUniversalSampling.select(&mut rng, &population).unwrap_err(),
+            SelectionError::AllFitnessZero,
+        );
+        assert_eq!(
+            RouletteWheelSelection.select(&mut rng, &[] as &[TestIndividual]).unwrap_err(),
+            SelectionError::EmptyPopulation,
+        );
+    }
+
+    #[test]
+    fn asking_for_nothing_always_succeeds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![TestIndividual::new(0.0); 4];
+
+        assert!(RouletteWheelSelection.select_many(&mut rng, &population, 0).unwrap().is_empty());
+        assert!(StochasticUniversalSampling.select_many(&mut rng, &population, 0).unwrap().is_empty());
+        assert!(RankSelection::linear(1.5).select_many(&mut rng, &[] as &[TestIndividual], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sus_skips_zero_fitness_and_overflow() {
+        // Always spins to 0.0, so the first pointer sits on the first boundary
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+
+        let selected = StochasticUniversalSampling.select_indices(&mut rng, &[0.0, 1.0, 0.0, 1.0], 2).unwrap();
+        assert!(selected.iter().all(|&index| index == 1 || index == 3));
+
+        assert_eq!(
+            StochasticUniversalSampling.select_indices(&mut rng, &[f32::MAX, f32::MAX], 2).unwrap_err(),
+            SelectionError::InvalidFitness,
+        );
+    }
+
+    #[test]
+    fn uniform_fallback_rescues_all_zero_population() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = vec![TestIndividual::new(0.0); 4];
+
+        let selection = Scaled::new(RouletteWheelSelection, UniformFallback);
+
+        assert_eq!(selection.select_many(&mut rng, &population, 10).unwrap().len(), 10);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::*;
+
+#[derive(Clone, Debug)]
+struct Species {
+    id: usize,
+    age: usize,
+    representative: Chromosome,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeciesStats {
+    // Stable across generations, so a niche can be followed over time
+    pub id: usize,
+    // Generations this species has existed for
+    pub age: usize,
+    pub size: usize,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+}
+
+/// Groups chromosomes into species by genome distance and shares fitness
+/// within each one, so a single crowded strategy can't take over the
+/// whole population.
+#[derive(Clone, Debug)]
+pub struct Speciation {
+    threshold: f32,
+    next_id: usize,
+    species: Vec<Species>,
+    stats: Vec<SpeciesStats>,
+}
+
+impl Speciation {
+    // Chromosomes closer than `threshold` (see `Chromosome::distance`) to a
+    // species' representative join that species
+    pub fn new(threshold: f32) -> Self {
+        assert!(threshold > 0.0);
+        Self {
+            threshold,
+            next_id: 0,
+            species: Vec::new(),
+            stats: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.species.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.species.is_empty()
+    }
+
+    // Per-species statistics from the last call to `speciate`
+    pub fn stats(&self) -> &[SpeciesStats] {
+        &self.stats
+    }
+
+    // Assigns every individual to a species and returns their species ids.
+    // Each surviving species' fittest member becomes its next representative.
+    pub fn speciate<I: Individual>(&mut self, population: &[I]) -> Vec<usize> {
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); self.species.len()];
+
+        for (index, individual) in population.iter().enumerate() {
+            let chromosome = individual.chromosome();
+            let found = self.species
+                .iter()
+                .position(|species| species.representative.distance(chromosome) < self.threshold);
+
+            match found {
+                Some(species) => members[species].push(index),
+                None => {
+                    self.species.push(Species {
+                        id: self.next_id,
+                        age: 0,
+                        representative: chromosome.clone(),
+                    });
+                    self.next_id += 1;
+                    members.push(vec![index]);
+                }
+            }
+        }
+
+        let mut ids = vec![0; population.len()];
+        let mut survivors = Vec::new();
+        self.stats.clear();
+
+        for (mut species, members) in self.species.drain(..).zip(members) {
+            if members.is_empty() {
+                continue;
+            }
+
+            let fitness: Vec<f32> = members.iter().map(|&index| population[index].fitness()).collect();
+            let best = best_first(&fitness)[0];
+
+            for &index in &members {
+                ids[index] = species.id;
+            }
+
+            self.stats.push(SpeciesStats {
+                id: species.id,
+                age: species.age,
+                size: members.len(),
+                best_fitness: fitness[best],
+                mean_fitness: fitness.iter().sum::<f32>() / fitness.len() as f32,
+            });
+
+            species.representative = population[members[best]].chromosome().clone();
+            species.age += 1;
+            survivors.push(species);
+        }
+
+        self.species = survivors;
+        ids
+    }
+
+    // Explicit fitness sharing: speciates, then divides every individual's
+    // fitness by the size of its species. Feed the result to
+    // `GeneticAlgorithm::evolve_with_fitness`.
+    pub fn shared_fitness<I: Individual>(&mut self, population: &[I]) -> Vec<f32> {
+        let ids = self.speciate(population);
+
+        population
+            .iter()
+            .zip(ids)
+            .map(|(individual, id)| {
+                let size = self.stats.iter().find(|stats| stats.id == id).unwrap().size;
+                individual.fitness() / size as f32
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn individual(fitness: f32, gene: f32) -> TestIndividual {
+        TestIndividual::with_chromosome(fitness, [gene, gene].into_iter().collect())
+    }
+
+    #[test]
+    fn distance_is_rms() {
+        let a: Chromosome = [0.0, 0.0].into_iter().collect();
+        let b: Chromosome = [3.0, 4.0].into_iter().collect();
+
+        assert_eq!(a.distance(&b), (12.5f32).sqrt());
+        assert_eq!(a.distance(&a), 0.0);
+    }
+
+    #[test]
+    fn shares_fitness_within_species() {
+        let mut speciation = Speciation::new(0.5);
+        // Three close together around 0.0, one loner at 5.0
+        let population = vec![
+            individual(3.0, 0.0),
+            individual(6.0, 0.1),
+            individual(3.0, -0.1),
+            individual(4.0, 5.0),
+        ];
+
+        let shared = speciation.shared_fitness(&population);
+
+        assert_eq!(shared, vec![1.0, 2.0, 1.0, 4.0]);
+        assert_eq!(speciation.len(), 2);
+        assert_eq!(speciation.stats()[0], SpeciesStats {
+            id: 0,
+            age: 0,
+            size: 3,
+            best_fitness: 6.0,
+            mean_fitness: 4.0,
+        });
+    }
+
+    #[test]
+    fn species_persist_and_die_out() {
+        let mut speciation = Speciation::new(0.5);
+
+        speciation.speciate(&[individual(1.0, 0.0), individual(1.0, 5.0)]);
+        let ids = speciation.speciate(&[individual(1.0, 0.2), individual(1.0, 0.3)]);
+
+        assert_eq!(ids, vec![0, 0]);
+        assert_eq!(speciation.len(), 1);
+        assert_eq!(speciation.stats()[0].age, 1);
+    }
+}
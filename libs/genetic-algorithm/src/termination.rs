@@ -0,0 +1,256 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Where a run stands after a finished generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    // Generations finished so far, starting at 1
+    pub generation: usize,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+    pub elapsed: Duration,
+}
+
+/// Why a run stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Termination {
+    MaxGenerations(usize),
+    TargetFitness(f32),
+    // No improvement for this many generations
+    Stagnation(usize),
+    TimeLimit(Duration),
+    // Every criterion of an `All` fired
+    All(Vec<Termination>),
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxGenerations(generations) => write!(f, "reached {} generations", generations),
+            Self::TargetFitness(target) => write!(f, "reached target fitness {}", target),
+            Self::Stagnation(window) => write!(f, "no improvement in {} generations", window),
+            Self::TimeLimit(limit) => write!(f, "ran out of time after {:?}", limit),
+            Self::All(reasons) => {
+                let reasons: Vec<String> = reasons.iter().map(|reason| reason.to_string()).collect();
+                write!(f, "{}", reasons.join(" and "))
+            }
+        }
+    }
+}
+
+pub trait TerminationCriterion {
+    // Called once after every generation, in order; criteria may keep state
+    fn check(&mut self, progress: &Progress) -> Option<Termination>;
+}
+
+#[derive(Debug)]
+pub struct MaxGenerations {
+    generations: usize,
+}
+
+impl MaxGenerations {
+    pub fn new(generations: usize) -> Self {
+        Self {
+            generations,
+        }
+    }
+}
+
+impl TerminationCriterion for MaxGenerations {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        (progress.generation >= self.generations).then_some(Termination::MaxGenerations(self.generations))
+    }
+}
+
+/// Stops once the best individual reaches `target`.
+#[derive(Debug)]
+pub struct TargetFitness {
+    target: f32,
+}
+
+impl TargetFitness {
+    pub fn new(target: f32) -> Self {
+        Self {
+            target,
+        }
+    }
+}
+
+impl TerminationCriterion for TargetFitness {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        (progress.best_fitness >= self.target).then_some(Termination::TargetFitness(self.target))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitnessMetric {
+    Best,
+    Mean,
+}
+
+/// Stops when `metric` hasn't beaten its best value by more than `tolerance`
+/// for `window` generations in a row.
+#[derive(Debug)]
+pub struct Stagnation {
+    window: usize,
+    tolerance: f32,
+    metric: FitnessMetric,
+    best: f32,
+    since_improvement: usize,
+}
+
+impl Stagnation {
+    pub fn new(window: usize, tolerance: f32, metric: FitnessMetric) -> Self {
+        assert!(window > 0);
+        assert!(tolerance >= 0.0);
+        Self {
+            window,
+            tolerance,
+            metric,
+            best: f32::NEG_INFINITY,
+            since_improvement: 0,
+        }
+    }
+}
+
+impl TerminationCriterion for Stagnation {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        let value = match self.metric {
+            FitnessMetric::Best => progress.best_fitness,
+            FitnessMetric::Mean => progress.mean_fitness,
+        };
+
+        if value > self.best + self.tolerance {
+            self.best = value;
+            self.since_improvement = 0;
+        } else {
+            self.since_improvement += 1;
+        }
+
+        (self.since_improvement >= self.window).then_some(Termination::Stagnation(self.window))
+    }
+}
+
+/// Stops once the run has taken `limit` of wall-clock time. Checked between
+/// generations, so the last generation may overshoot.
+#[derive(Debug)]
+pub struct TimeLimit {
+    limit: Duration,
+}
+
+impl TimeLimit {
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+        }
+    }
+}
+
+impl TerminationCriterion for TimeLimit {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        (progress.elapsed >= self.limit).then_some(Termination::TimeLimit(self.limit))
+    }
+}
+
+/// Fires as soon as any criterion does, reporting the first one in order.
+pub struct Any {
+    criteria: Vec<Box<dyn TerminationCriterion>>,
+}
+
+impl Any {
+    pub fn new(criteria: Vec<Box<dyn TerminationCriterion>>) -> Self {
+        assert!(!criteria.is_empty());
+        Self {
+            criteria,
+        }
+    }
+}
+
+impl TerminationCriterion for Any {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        // Check everything so stateful criteria see every generation
+        let fired: Vec<Termination> = self.criteria
+            .iter_mut()
+            .filter_map(|criterion| criterion.check(progress))
+            .collect();
+        fired.into_iter().next()
+    }
+}
+
+/// Fires only when every criterion fires in the same generation.
+pub struct All {
+    criteria: Vec<Box<dyn TerminationCriterion>>,
+}
+
+impl All {
+    pub fn new(criteria: Vec<Box<dyn TerminationCriterion>>) -> Self {
+        assert!(!criteria.is_empty());
+        Self {
+            criteria,
+        }
+    }
+}
+
+impl TerminationCriterion for All {
+    fn check(&mut self, progress: &Progress) -> Option<Termination> {
+        let fired: Vec<Termination> = self.criteria
+            .iter_mut()
+            .filter_map(|criterion| criterion.check(progress))
+            .collect();
+        (fired.len() == self.criteria.len()).then_some(Termination::All(fired))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(generation: usize, best_fitness: f32, mean_fitness: f32) -> Progress {
+        Progress {
+            generation,
+            best_fitness,
+            mean_fitness,
+            elapsed: Duration::from_secs(generation as u64),
+        }
+    }
+
+    #[test]
+    fn simple_criteria() {
+        assert_eq!(MaxGenerations::new(3).check(&progress(2, 0.0, 0.0)), None);
+        assert_eq!(MaxGenerations::new(3).check(&progress(3, 0.0, 0.0)), Some(Termination::MaxGenerations(3)));
+
+        assert_eq!(TargetFitness::new(10.0).check(&progress(1, 9.0, 0.0)), None);
+        assert_eq!(TargetFitness::new(10.0).check(&progress(1, 10.0, 0.0)), Some(Termination::TargetFitness(10.0)));
+
+        let limit = Duration::from_secs(5);
+        assert_eq!(TimeLimit::new(limit).check(&progress(4, 0.0, 0.0)), None);
+        assert_eq!(TimeLimit::new(limit).check(&progress(5, 0.0, 0.0)), Some(Termination::TimeLimit(limit)));
+    }
+
+    #[test]
+    fn stagnation_resets_on_improvement() {
+        let mut stagnation = Stagnation::new(2, 0.5, FitnessMetric::Mean);
+        let means = [1.0, 1.2, 2.0, 2.3, 2.4];
+        let fired: Vec<bool> = means
+            .iter()
+            .enumerate()
+            .map(|(i, &mean)| stagnation.check(&progress(i + 1, 0.0, mean)).is_some())
+            .collect();
+
+        assert_eq!(fired, vec![false, false, false, false, true]);
+    }
+
+    #[test]
+    fn composites() {
+        let mut any = Any::new(vec![Box::new(TargetFitness::new(10.0)), Box::new(MaxGenerations::new(2))]);
+        assert_eq!(any.check(&progress(1, 0.0, 0.0)), None);
+        assert_eq!(any.check(&progress(2, 0.0, 0.0)), Some(Termination::MaxGenerations(2)));
+
+        let mut all = All::new(vec![Box::new(TargetFitness::new(10.0)), Box::new(MaxGenerations::new(2))]);
+        assert_eq!(all.check(&progress(2, 0.0, 0.0)), None);
+        assert_eq!(
+            all.check(&progress(2, 10.0, 0.0)),
+            Some(Termination::All(vec![Termination::TargetFitness(10.0), Termination::MaxGenerations(2)])),
+        );
+    }
+}
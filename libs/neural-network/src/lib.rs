@@ -3,6 +3,7 @@ mod util;
 
 use rand::{Rng, RngCore};
 use std::iter::once;
+use std::ops::Range;
 
 extern crate approx;
 
@@ -11,6 +12,21 @@ pub struct LayerTopology {
     pub neurons: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    Weights,
+    Bias,
+}
+
+// Where one neuron's incoming weights, or its bias, sit in `weights()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamSegment {
+    pub layer: usize,
+    pub neuron: usize,
+    pub kind: ParamKind,
+    pub range: Range<usize>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Network {
     layers: Vec<Layer>
@@ -44,6 +60,32 @@ impl Network {
             .copied()
     }
 
+    // Each neuron is stored as its bias followed by its weights
+    pub fn layout(&self) -> Vec<ParamSegment> {
+        let mut offset = 0;
+        let mut segments = Vec::new();
+
+        for (layer, Layer { neurons }) in self.layers.iter().enumerate() {
+            for (neuron, Neuron { weights, .. }) in neurons.iter().enumerate() {
+                segments.push(ParamSegment {
+                    layer,
+                    neuron,
+                    kind: ParamKind::Bias,
+                    range: offset..offset + 1,
+                });
+                segments.push(ParamSegment {
+                    layer,
+                    neuron,
+                    kind: ParamKind::Weights,
+                    range: offset + 1..offset + 1 + weights.len(),
+                });
+                offset += 1 + weights.len();
+            }
+        }
+
+        segments
+    }
+
     pub fn from_weights(
         layers: &[LayerTopology],
         weights: impl IntoIterator<Item = f32>
@@ -36,6 +36,36 @@ impl MatrixNetwork {
             })
     }
 
+    // Each layer is stored as its weight matrix, one row per neuron,
+    // followed by all of its biases
+    pub fn layout(&self) -> Vec<ParamSegment> {
+        let mut offset = 0;
+        let mut segments = Vec::new();
+
+        for (layer, matrix) in self.layers.iter().enumerate() {
+            let biases = offset + matrix.num_inputs * matrix.num_outputs;
+
+            for neuron in 0..matrix.num_outputs {
+                segments.push(ParamSegment {
+                    layer,
+                    neuron,
+                    kind: ParamKind::Weights,
+                    range: offset + neuron * matrix.num_inputs..offset + (neuron + 1) * matrix.num_inputs,
+                });
+                segments.push(ParamSegment {
+                    layer,
+                    neuron,
+                    kind: ParamKind::Bias,
+                    range: biases + neuron..biases + neuron + 1,
+                });
+            }
+
+            offset = biases + matrix.num_outputs;
+        }
+
+        segments
+    }
+
     pub fn from_weights(
         layers: &[LayerTopology],
         weights: impl IntoIterator<Item = f32>
@@ -83,12 +113,10 @@ pub struct MatrixLayer {
 impl MatrixLayer {
     fn random(rng: &mut dyn RngCore, num_inputs: usize, num_outputs: usize) -> Self {
         let weights = (0..num_inputs * num_outputs)
-            .into_iter()
             .map(|_| rng.gen_range(-1.0..=1.0) as f32)
             .collect();
 
         let bias = (0..num_outputs)
-            .into_iter()
             .map(|_| rng.gen_range(-1.0..=1.0) as f32)
             .collect();
 
@@ -154,4 +182,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    pub fn testing_layout() {
+        let topology  = [
+            LayerTopology{
+                neurons: 3
+            },
+            LayerTopology {
+                neurons: 2
+            },
+        ];
+        let network = MatrixNetwork::from_weights(&topology, (0..8).map(|w| w as f32));
+        let weights = network.weights().collect::<Vec<f32>>();
+        let layout = network.layout();
+
+        // Every weight is covered exactly once
+        let mut covered = layout.iter().flat_map(|segment| segment.range.clone()).collect::<Vec<_>>();
+        covered.sort();
+        assert_eq!(covered, (0..weights.len()).collect::<Vec<_>>());
+
+        // Second neuron: row 3..6 of the matrix, bias is the last value
+        assert_eq!(layout[2], ParamSegment { layer: 0, neuron: 1, kind: ParamKind::Weights, range: 3..6 });
+        assert_eq!(layout[3], ParamSegment { layer: 0, neuron: 1, kind: ParamKind::Bias, range: 7..8 });
+    }
 }
@@ -15,7 +15,7 @@ pub fn matrix_vector_mult(matrix: &[f32], vector: &[f32], num_rows: usize, num_c
 
 pub fn vector_vector_add(vector1: &[f32], vector2: &[f32]) -> Vec<f32> {
     assert_eq!(vector1.len(), vector2.len());
-    vector1.into_iter()
+    vector1.iter()
         .zip(vector2)
         .map(|(val1, val2)| val1 + val2)
         .collect()
@@ -29,7 +29,7 @@ mod test {
     fn testing_matrix_vector_mult() {
         // Assume 2x3 matrix 
         let matrix: Vec<f32> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
-        let vec: Vec<f32> = vec![1.0, 1.0];
+        let vec: Vec<f32> = vec![1.0, 1.0, 1.0];
         let num_rows = 2;
         let num_cols = 3;
         matrix_vector_mult(&matrix, &vec, num_rows, num_cols);
@@ -1,6 +1,6 @@
 use lib_config::{Config, ConfigRange};
 use lib_simulation::{Simulation, Statistics};
-use rand::{Rng, RngCore};
+use rand::RngCore;
 
 pub struct Agent {
     pub simulation: Simulation,
@@ -1,11 +1,10 @@
 mod agent;
 
-use lib_simulation::{Simulation, Statistics};
+use lib_simulation::Simulation;
 use agent::Agent;
 use lib_config::{Config, ConfigRange};
 use rand::rngs::ThreadRng;
 use rand::{thread_rng, Rng, RngCore};
-use core::num;
 
 pub struct Optimizer {
     population: Vec<Agent>,
@@ -35,7 +34,7 @@ impl Optimizer {
             config_range,
             num_agents,
             time: 0,
-            rng: rng,
+            rng,
         }
     }
 
@@ -45,7 +44,8 @@ impl Optimizer {
 
     // At each step, let the simulations of each agent train for num_gens times
     // Then sort the population vector by weighted score in non-descending order
-    fn step(&mut self, num_gens: u32, ) {
+    #[allow(unused)]
+    fn step(&mut self, num_gens: u32) {
         // Advance the population forward num_gens times
         for agent in self.population.iter_mut() {
             for _ in 0..num_gens {
@@ -64,14 +64,14 @@ impl Optimizer {
         self.time += 1;
     }
 
-    fn pbt(rng: &mut dyn RngCore, population: &mut Vec<Agent>, num_agents: usize, config_range: &ConfigRange) {
+    fn pbt(rng: &mut dyn RngCore, population: &mut [Agent], num_agents: usize, config_range: &ConfigRange) {
         // Exploit
         Self::truncation_selection(rng, population, num_agents);
         // Explore
-        Self::perturb(rng, population, num_agents, config_range);
+        Self::perturb(rng, population, config_range);
     }
 
-    fn truncation_selection(rng: &mut dyn RngCore, population: &mut Vec<Agent>, num_agents: usize) {
+    fn truncation_selection(rng: &mut dyn RngCore, population: &mut [Agent], num_agents: usize) {
         let threshold = (population.len() as f32 * 0.20) as usize;
         assert!(threshold >= 1);
 
@@ -80,11 +80,11 @@ impl Optimizer {
             let fast_index = rng.gen_range(num_agents - threshold..num_agents);
             
             // Copy over hyperparams
-            population[i].config = population[fast_index].config.clone();
+            population[i].config = population[fast_index].config;
         }
     }
 
-    fn perturb(rng: &mut dyn RngCore, population: &mut Vec<Agent>, num_agents: usize, config_range: &ConfigRange) {
+    fn perturb(rng: &mut dyn RngCore, population: &mut [Agent], config_range: &ConfigRange) {
         // Perturb hyperparams        
         for agent in population.iter_mut() {
             let up_down = (0..6)
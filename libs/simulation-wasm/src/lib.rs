@@ -16,8 +16,8 @@ impl Simulation {
         let sim = sim::Simulation::random(&mut rng);
 
         Self {
-            rng: rng,
-            sim: sim,
+            rng,
+            sim,
         }
     }
 
@@ -32,6 +32,20 @@ impl Simulation {
     pub fn train(&mut self) -> Statistics {
         Statistics::from_other(self.sim.train(&mut self.rng))
     }
+
+    // Filled MAP-Elites cells, empty unless MAP-Elites is enabled
+    pub fn elites(&self) -> Vec<Elite> {
+        self.sim
+            .map_elites()
+            .map(|archive| archive.elites().map(Elite::from).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -97,6 +111,26 @@ impl From<&sim::Food> for Food {
     }
 }
 
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Elite {
+    // First two behavior components, the grid's axes
+    pub x: f32,
+    pub y: f32,
+    pub fitness: f32,
+}
+
+impl From<&lib_genetic_algorithm::map_elites::Elite> for Elite {
+    fn from(elite: &lib_genetic_algorithm::map_elites::Elite) -> Self {
+        let behavior = elite.behavior();
+        Self {
+            x: behavior[0],
+            y: behavior.get(1).copied().unwrap_or(0.0),
+            fitness: elite.fitness(),
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct Statistics {
@@ -11,6 +11,12 @@ pub struct Animal {
     pub(crate) brain: MatrixBrain,
 
     pub(crate) satiation: usize,
+    pub(crate) distance: f32,
+    // Total absolute rotation, in radians
+    pub(crate) turning: f32,
+
+    // Carried between generations for self-adaptive mutation
+    pub(crate) step_sizes: Vec<f32>,
 }
 
 impl Animal {
@@ -22,6 +28,9 @@ impl Animal {
             eye,
             brain,
             satiation: 0,
+            distance: 0.0,
+            turning: 0.0,
+            step_sizes: Vec::new(),
         }
     }
 
@@ -47,17 +56,58 @@ impl Animal {
         self.rotation
     }
 
+    // Steers towards whatever `targets` the eye picks up
+    pub(crate) fn think(&mut self, targets: &[Food]) {
+        let vision = self.eye.process_vision(self.position, self.rotation, targets);
+        let response = self.brain.nn.forward(vision);
+
+        let speed = response[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
+        let rotation = response[1].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
+
+        self.speed = (self.speed + speed).clamp(SPEED_MIN, SPEED_MAX);
+        self.rotation = na::Rotation2::new(self.rotation.angle() + rotation);
+        self.turning += rotation.abs();
+    }
+
+    pub(crate) fn advance(&mut self) {
+        self.position += self.rotation * na::Vector2::new(0.0, self.speed);
+        self.distance += self.speed;
+
+        self.position.x = na::wrap(self.position.x, 0.0, 1.0);
+        self.position.y = na::wrap(self.position.y, 0.0, 1.0);
+    }
+
     pub(crate) fn as_chromosome(&self) -> Chromosome {
-        self.brain.as_chromosome()
+        let mut chromosome = self.brain.as_chromosome();
+        chromosome.set_step_sizes(self.step_sizes.clone());
+        chromosome
     }
 
+    // `configs` are the ones the population was built from. The eye comes
+    // from the first of them with the brain's topology, so its field of view
+    // survives breeding; brains none of them describe get the default one.
     pub(crate) fn from_chromosome(
         chromosome: Chromosome,
+        configs: &[Config],
         rng: &mut dyn RngCore
     ) -> Self {
-        let eye = Eye::default();
+        let eye = match chromosome.layout() {
+            Some(layout) => {
+                let layer_sizes = layout.layer_sizes();
+                configs
+                    .iter()
+                    .find(|&&config| MatrixBrain::layer_sizes(config) == layer_sizes)
+                    .map(|&config| Eye::from_config(config))
+                    .unwrap_or_else(|| Eye::with_cells(layer_sizes[0]))
+            }
+            None => Eye::default(),
+        };
+        let step_sizes = chromosome.step_sizes().to_vec();
         let brain = MatrixBrain::from_chromosome(chromosome, &eye);
 
-        Self::new(eye, brain, rng)
+        Self {
+            step_sizes,
+            ..Self::new(eye, brain, rng)
+        }
     }
 }
\ No newline at end of file
@@ -1,7 +1,10 @@
 use crate::*;
 
+#[derive(Clone)]
 pub struct AnimalIndividual {
     pub(crate) fitness: f32,
+    // Food eaten, distance travelled and brain size; see `from_animal`
+    pub(crate) objectives: Vec<f32>,
     pub(crate) chromosome: Chromosome
 }
 
@@ -9,6 +12,7 @@ impl ga::Individual for AnimalIndividual {
     fn create(chromosome: Chromosome) -> Self {
         Self {
             fitness: 0.0,
+            objectives: Vec::new(),
             chromosome,
         }
     }
@@ -22,15 +26,30 @@ impl ga::Individual for AnimalIndividual {
     }
 }
 
+impl ga::nsga2::MultiObjectiveIndividual for AnimalIndividual {
+    fn objectives(&self) -> Vec<f32> {
+        self.objectives.clone()
+    }
+}
+
 impl AnimalIndividual {
     pub fn from_animal(animal: &Animal) -> Self {
+        let chromosome = animal.as_chromosome();
+
+        // All objectives are maximised, so movement and brain size (total
+        // absolute weight) are negated to reward using less of them
+        let brain_size: f32 = chromosome.iter().map(|weight| weight.abs()).sum();
+        let objectives = vec![animal.satiation as f32, -animal.distance, -brain_size];
+
         Self {
             fitness: animal.satiation as f32,
-            chromosome: animal.as_chromosome(),
+            objectives,
+            chromosome,
         }
     }
 
-    pub fn into_animal(self, rng: &mut dyn RngCore) -> Animal {
-        Animal::from_chromosome(self.chromosome, rng)
+    // See `Animal::from_chromosome` for what `configs` are for
+    pub fn into_animal(self, configs: &[Config], rng: &mut dyn RngCore) -> Animal {
+        Animal::from_chromosome(self.chromosome, configs, rng)
     }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use crate::*;
+
+/// What novelty search compares animals by, or MAP-Elites grids them by.
+/// Components are scaled to roughly 0..=1 unless noted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BehaviorDescriptor {
+    // Where the animal ended the generation
+    FinalPosition,
+    // Average speed and average turning per step
+    Movement,
+    // Both of the above
+    Full,
+    // Food eaten (unscaled) and average speed
+    Foraging,
+}
+
+impl Animal {
+    pub fn behavior(&self, descriptor: BehaviorDescriptor) -> Vec<f32> {
+        let movement = [
+            self.distance / (GEN_LEN as f32 * SPEED_MAX),
+            self.turning / (GEN_LEN as f32 * ROTATION_ACCEL),
+        ];
+
+        match descriptor {
+            BehaviorDescriptor::FinalPosition => vec![self.position.x, self.position.y],
+            BehaviorDescriptor::Movement => movement.to_vec(),
+            BehaviorDescriptor::Full => vec![self.position.x, self.position.y, movement[0], movement[1]],
+            BehaviorDescriptor::Foraging => vec![self.satiation as f32, movement[0]],
+        }
+    }
+}
+
+/// Replaces each animal's fitness with a blend of satiation and novelty.
+#[derive(Clone, Debug)]
+pub struct NoveltySearch {
+    descriptor: BehaviorDescriptor,
+    archive: ga::novelty::NoveltyArchive,
+    // 0 is pure satiation, 1 pure novelty
+    weight: f32,
+}
+
+impl NoveltySearch {
+    pub fn new(descriptor: BehaviorDescriptor, archive: ga::novelty::NoveltyArchive, weight: f32) -> Self {
+        assert!((0.0..=1.0).contains(&weight));
+        Self {
+            descriptor,
+            archive,
+            weight,
+        }
+    }
+
+    pub fn archive(&self) -> &ga::novelty::NoveltyArchive {
+        &self.archive
+    }
+
+    pub(crate) fn apply(&mut self, animals: &[Animal], population: &mut [AnimalIndividual]) {
+        let behaviors: Vec<Vec<f32>> = animals
+            .iter()
+            .map(|animal| animal.behavior(self.descriptor))
+            .collect();
+        let novelty = self.archive.evaluate(&behaviors);
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness).collect();
+
+        for (individual, blended) in population.iter_mut().zip(ga::novelty::blend(&fitness, &novelty, self.weight)) {
+            individual.fitness = blended;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn spinning_in_place_is_told_apart() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut spinner = Animal::random(&mut rng);
+        let mut runner = spinner.clone();
+        spinner.turning = GEN_LEN as f32 * ROTATION_ACCEL;
+        runner.distance = GEN_LEN as f32 * SPEED_MAX;
+
+        assert_eq!(spinner.behavior(BehaviorDescriptor::Movement), vec![0.0, 1.0]);
+        assert_eq!(runner.behavior(BehaviorDescriptor::Movement), vec![1.0, 0.0]);
+        assert_eq!(runner.behavior(BehaviorDescriptor::Full).len(), 4);
+    }
+
+    #[test]
+    fn pure_novelty_ignores_satiation() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut animals: Vec<Animal> = (0..3).map(|_| Animal::random(&mut rng)).collect();
+        animals[0].satiation = 10;
+        animals[2].turning = GEN_LEN as f32 * ROTATION_ACCEL;
+
+        let mut population: Vec<_> = animals.iter().map(AnimalIndividual::from_animal).collect();
+        let archive = ga::novelty::NoveltyArchive::new(1, 0.5, 10);
+        let mut novelty = NoveltySearch::new(BehaviorDescriptor::Movement, archive, 1.0);
+        novelty.apply(&animals, &mut population);
+
+        let fitness: Vec<f32> = population.iter().map(|individual| individual.fitness).collect();
+        assert_eq!(fitness, vec![0.0, 0.0, 1.0]);
+        assert_eq!(novelty.archive().len(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 use lib_neural_network::{matrix_network::MatrixNetwork, LayerTopology};
+use std::sync::Arc;
 
 use crate::*;
 
@@ -38,7 +39,13 @@ impl Brain {
 
 
     pub(crate) fn as_chromosome(&self) -> Chromosome {
-        self.nn.weights().collect()
+        let mut chromosome: Chromosome = self.nn.weights().collect();
+        chromosome.set_layout(Arc::new(self.gene_layout()));
+        chromosome
+    }
+
+    pub fn gene_layout(&self) -> GeneLayout {
+        gene_layout(self.nn.layout())
     }
 
     pub(crate) fn from_chromosome(
@@ -81,38 +88,51 @@ impl MatrixBrain {
     }
 
     pub fn from_config(rng: &mut dyn RngCore, config: Config) -> Self {
-        let mut top: Vec<LayerTopology> = Vec::new();
-        top.push(LayerTopology {
-            neurons: config.num_eye_cells,
-        });
-
-        for _ in 0..config.num_hidden_layers {
-            top.push(LayerTopology {
-                neurons: config.hidden_layer_size,
-            })
-        }
-
-        top.push(LayerTopology{
-            neurons: 2
-        });
+        let top: Vec<LayerTopology> = Self::layer_sizes(config)
+            .into_iter()
+            .map(|neurons| LayerTopology { neurons })
+            .collect();
 
         MatrixBrain {
             nn: MatrixNetwork::random(rng, top.as_slice())
         }
     }
 
+    // Neurons per layer of brains built from `config`, inputs first
+    pub(crate) fn layer_sizes(config: Config) -> Vec<usize> {
+        let mut sizes = vec![config.num_eye_cells];
+        sizes.extend(std::iter::repeat(config.hidden_layer_size).take(config.num_hidden_layers));
+        sizes.push(2);
+        sizes
+    }
+
     pub(crate) fn as_chromosome(&self) -> Chromosome {
-        self.nn.weights().collect()
+        let mut chromosome: Chromosome = self.nn.weights().collect();
+        chromosome.set_layout(Arc::new(self.gene_layout()));
+        chromosome
     }
 
+    pub fn gene_layout(&self) -> GeneLayout {
+        gene_layout(self.nn.layout())
+    }
+
+    // Rebuilds the topology from the chromosome's layout when it has one,
+    // so brains of different shapes survive breeding
     pub(crate) fn from_chromosome(
         chromosome: Chromosome,
         eye: &Eye
     ) -> Self {
+        let topology: Vec<LayerTopology> = match chromosome.layout() {
+            Some(layout) => layout
+                .layer_sizes()
+                .into_iter()
+                .map(|neurons| LayerTopology { neurons })
+                .collect(),
+            None => Vec::from(Self::topology(eye)),
+        };
+
         Self {
-            nn: mn::MatrixNetwork::from_weights(
-                &Self::topology(eye), 
-            chromosome,)
+            nn: mn::MatrixNetwork::from_weights(&topology, chromosome)
         }
     }
 
@@ -129,4 +149,19 @@ impl MatrixBrain {
             },
         ]
     }
-}
\ No newline at end of file
+}
+
+fn gene_layout(segments: Vec<nn::ParamSegment>) -> GeneLayout {
+    segments
+        .into_iter()
+        .map(|segment| GeneSegment {
+            layer: segment.layer,
+            neuron: segment.neuron,
+            kind: match segment.kind {
+                nn::ParamKind::Weights => GeneKind::Weights,
+                nn::ParamKind::Bias => GeneKind::Bias,
+            },
+            range: segment.range,
+        })
+        .collect()
+}
@@ -0,0 +1,324 @@
+use crate::*;
+use rand::seq::SliceRandom;
+
+const CATCH_DISTANCE: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interaction {
+    // Both species forage for the same food
+    Competition,
+    // The second species hunts the first, which forages. A catch feeds the
+    // hunter, costs the prey one unit of satiation and respawns it elsewhere.
+    PredatorPrey,
+}
+
+type Fitness = dyn Fn(&Animal) -> f32;
+
+/// One of the two populations in a `Coevolution`, with its own genetic
+/// algorithm, fitness and hall of fame.
+pub struct Species {
+    size: usize,
+    config: Option<Config>,
+    ga: ga::GeneticAlgorithm<Selection>,
+    // Satiation unless set otherwise
+    fitness: Box<Fitness>,
+    hall_of_fame: ga::hall_of_fame::HallOfFame,
+    report: ga::evolution_report::EvolutionReport,
+}
+
+impl Species {
+    pub fn random(size: usize) -> Self {
+        Self::with_mutation(size, None, mutation_method::GaussianMutation::new(0.01, 0.03))
+    }
+
+    pub fn from_config(size: usize, config: Config) -> Self {
+        Self::with_mutation(size, Some(config), mutation_method::GaussianMutation::from_config(config))
+    }
+
+    fn with_mutation(size: usize, config: Option<Config>, mutation_method: mutation_method::GaussianMutation) -> Self {
+        assert!(size > 0);
+        let ga = ga::GeneticAlgorithm::new(
+            Selection::default(),
+            crossover_method::UniformCrossover,
+            mutation_method,
+        );
+
+        Self {
+            size,
+            config,
+            ga,
+            fitness: Box::new(|animal| animal.satiation as f32),
+            hall_of_fame: ga::hall_of_fame::HallOfFame::new(HALL_OF_FAME_SIZE),
+            report: Default::default(),
+        }
+    }
+
+    pub fn with_fitness(mut self, fitness: impl Fn(&Animal) -> f32 + 'static) -> Self {
+        self.fitness = Box::new(fitness);
+        self
+    }
+
+    pub fn with_replacement(mut self, replacement: impl ga::replacement::ReplacementStrategy + 'static) -> Self {
+        self.ga = self.ga.with_replacement(replacement);
+        self
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn hall_of_fame(&self) -> &ga::hall_of_fame::HallOfFame {
+        &self.hall_of_fame
+    }
+
+    pub fn evolution_report(&self) -> &ga::evolution_report::EvolutionReport {
+        &self.report
+    }
+
+    fn spawn(&self, rng: &mut dyn RngCore) -> Animal {
+        match self.config {
+            Some(config) => Animal::from_config(rng, config),
+            None => Animal::random(rng),
+        }
+    }
+
+    // Breeds the next generation from `animals`, then swaps the last
+    // `opponents` children for random hall-of-fame members so the other
+    // species keeps meeting strategies that worked before, instead of
+    // cycling through ones it has already beaten
+    fn evolve(&mut self, rng: &mut dyn RngCore, animals: &[Animal], opponents: usize) -> Vec<Animal> {
+        let population: Vec<_> = animals
+            .iter()
+            .map(|animal| AnimalIndividual {
+                fitness: (self.fitness)(animal),
+                ..AnimalIndividual::from_animal(animal)
+            })
+            .collect();
+        self.hall_of_fame.record(&population);
+
+        let (children, report) = self.ga
+            .evolve(rng, &population)
+            .expect("Species has no animals");
+        self.report = report;
+
+        let mut animals: Vec<Animal> = children
+            .into_iter()
+            .map(|individual| individual.into_animal(self.config.as_slice(), rng))
+            .collect();
+
+        let champions: Vec<_> = self.hall_of_fame.iter().collect();
+        let num_children = animals.len();
+        for animal in &mut animals[num_children - opponents.min(num_children)..] {
+            if let Some(champion) = champions.choose(rng) {
+                *animal = Animal::from_chromosome(champion.chromosome().clone(), self.config.as_slice(), rng);
+            }
+        }
+        if opponents > 0 {
+            self.ga.forget_lineage();
+        }
+
+        animals
+    }
+}
+
+/// Two species evolving against each other in one `World`. The world's
+/// animals hold the first species followed by the second.
+pub struct Coevolution {
+    world: World,
+    species: [Species; 2],
+    interaction: Interaction,
+    // Children per species replaced by its own hall of fame each generation
+    hall_of_fame_opponents: usize,
+    generation: usize,
+    age: usize,
+}
+
+impl Coevolution {
+    pub fn new(rng: &mut dyn RngCore, first: Species, second: Species, interaction: Interaction) -> Self {
+        let mut animals: Vec<Animal> = (0..first.size).map(|_| first.spawn(rng)).collect();
+        animals.extend((0..second.size).map(|_| second.spawn(rng)));
+        let foods = (0..NUM_FOODS).map(|_| Food::random(rng)).collect();
+
+        Self {
+            world: World {
+                animals,
+                foods,
+            },
+            species: [first, second],
+            interaction,
+            hall_of_fame_opponents: 0,
+            generation: 0,
+            age: 0,
+        }
+    }
+
+    pub fn with_hall_of_fame_opponents(mut self, count: usize) -> Self {
+        self.hall_of_fame_opponents = count;
+        self
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn species(&self, index: usize) -> &Species {
+        &self.species[index]
+    }
+
+    pub fn animals(&self, index: usize) -> &[Animal] {
+        &self.world.animals[self.range(index)]
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Perform a single step forward, returning each species' statistics at
+    // the end of a generation
+    pub fn step(&mut self, rng: &mut dyn RngCore) -> Option<[Statistics; 2]> {
+        self.process_collisions(rng);
+        self.process_brains();
+        for animal in &mut self.world.animals {
+            animal.advance();
+        }
+
+        self.age += 1;
+        if self.age > GEN_LEN {
+            let stats = [0, 1].map(|index| Statistics::find_stats(self.animals(index)));
+            self.evolve(rng);
+            self.generation += 1;
+
+            Some(stats)
+        } else {
+            None
+        }
+    }
+
+    pub fn train(&mut self, rng: &mut dyn RngCore) -> [Statistics; 2] {
+        loop {
+            if let Some(stats) = self.step(rng) {
+                return stats;
+            }
+        }
+    }
+
+    fn range(&self, index: usize) -> std::ops::Range<usize> {
+        let start = if index == 0 {0} else {self.species[0].size};
+        start..start + self.species[index].size
+    }
+
+    fn evolve(&mut self, rng: &mut dyn RngCore) {
+        self.age = 0;
+
+        let mut animals = Vec::with_capacity(self.world.animals.len());
+        for index in 0..2 {
+            let range = self.range(index);
+            let current = &self.world.animals[range];
+            animals.extend(self.species[index].evolve(rng, current, self.hall_of_fame_opponents));
+        }
+        self.world.animals = animals;
+
+        for food in &mut self.world.foods {
+            food.position = rng.gen();
+        }
+    }
+
+    fn process_collisions(&mut self, rng: &mut dyn RngCore) {
+        let split = self.species[0].size;
+        let foragers = match self.interaction {
+            Interaction::Competition => self.world.animals.len(),
+            Interaction::PredatorPrey => split,
+        };
+
+        for animal in &mut self.world.animals[..foragers] {
+            for food in &mut self.world.foods {
+                if na::distance(&animal.position, &food.position) <= EAT_DISTANCE {
+                    animal.satiation += 1;
+                    food.position = rng.gen();
+                }
+            }
+        }
+
+        if self.interaction == Interaction::PredatorPrey {
+            let (prey, predators) = self.world.animals.split_at_mut(split);
+            for predator in predators {
+                for prey in prey.iter_mut() {
+                    if na::distance(&predator.position, &prey.position) <= CATCH_DISTANCE {
+                        predator.satiation += 1;
+                        prey.satiation = prey.satiation.saturating_sub(1);
+                        prey.position = rng.gen();
+                    }
+                }
+            }
+        }
+    }
+
+    // Predators see prey where foragers see food
+    fn process_brains(&mut self) {
+        match self.interaction {
+            Interaction::Competition => {
+                for animal in &mut self.world.animals {
+                    animal.think(&self.world.foods);
+                }
+            }
+            Interaction::PredatorPrey => {
+                let (prey, predators) = self.world.animals.split_at_mut(self.species[0].size);
+                let targets: Vec<Food> = prey.iter().map(|animal| Food { position: animal.position }).collect();
+
+                for animal in prey {
+                    animal.think(&self.world.foods);
+                }
+                for animal in predators {
+                    animal.think(&targets);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn predators_catch_prey() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut coevolution = Coevolution::new(&mut rng, Species::random(3), Species::random(2), Interaction::PredatorPrey);
+        coevolution.world.foods.clear();
+
+        let hideout = coevolution.world.animals[1].position;
+        coevolution.world.animals[1].satiation = 2;
+        coevolution.world.animals[4].position = hideout;
+        coevolution.process_collisions(&mut rng);
+
+        assert_eq!(coevolution.animals(1)[1].satiation, 1);
+        assert_eq!(coevolution.animals(0)[1].satiation, 1);
+        assert_ne!(coevolution.animals(0)[1].position, hideout);
+    }
+
+    #[test]
+    fn species_evolve_separately() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut coevolution = Coevolution::new(&mut rng, Species::random(4), Species::random(3), Interaction::Competition)
+            .with_hall_of_fame_opponents(1);
+
+        for (index, animal) in coevolution.world.animals.iter_mut().enumerate() {
+            animal.satiation = index;
+        }
+        coevolution.evolve(&mut rng);
+
+        assert_eq!(coevolution.animals(0).len(), 4);
+        assert_eq!(coevolution.animals(1).len(), 3);
+        assert_eq!(coevolution.species(0).hall_of_fame().best().unwrap().fitness(), 3.0);
+        assert_eq!(coevolution.species(1).hall_of_fame().best().unwrap().fitness(), 6.0);
+
+        let opponent = coevolution.animals(1)[2].as_chromosome();
+        assert!(coevolution
+            .species(1)
+            .hall_of_fame()
+            .iter()
+            .any(|entry| entry.chromosome().as_slice() == opponent.as_slice()));
+    }
+}
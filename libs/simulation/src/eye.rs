@@ -34,10 +34,23 @@ impl Eye {
         }
     }
 
+    // Default field of view with `cells` cells
+    pub(crate) fn with_cells(cells: usize) -> Self {
+        Self::new(FOV_RANGE, FOV_ANGLE, cells)
+    }
+
     pub fn cells(&self) -> usize {
         self.cells
     }
 
+    pub fn fov_range(&self) -> f32 {
+        self.fov_range
+    }
+
+    pub fn fov_angle(&self) -> f32 {
+        self.fov_angle
+    }
+
     pub fn process_vision(&self,
         position: na::Point2<f32>,
         rotation: na::Rotation2<f32>,
@@ -0,0 +1,266 @@
+use crate::*;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    // Island i receives migrants from island i - 1
+    Ring,
+    // Every island receives migrants from every other island
+    FullyConnected,
+    // Every island receives migrants from one other island, picked anew each time
+    Random,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationPolicy {
+    BestReplaceWorst,
+    BestReplaceRandom,
+    RandomReplaceRandom,
+}
+
+/// Runs several `Simulation`s side by side on rayon's thread pool and copies
+/// animals between them every few generations. Islands may use different
+/// `Config`s, but must share a brain topology so migrants can breed with the
+/// locals.
+pub struct Archipelago {
+    islands: Vec<Simulation>,
+    topology: Topology,
+    policy: MigrationPolicy,
+    // Generations between migrations
+    interval: usize,
+    // Fraction of each island's population sent per source island
+    migration_rate: f32,
+    generation: usize,
+}
+
+impl Archipelago {
+    pub fn new(islands: Vec<Simulation>) -> Self {
+        assert!(islands.len() > 1);
+        let topologies: Vec<Vec<usize>> = islands
+            .iter()
+            .flat_map(|island| &island.world.animals)
+            .map(|animal| animal.as_chromosome().layout().map(|layout| layout.layer_sizes()).unwrap_or_default())
+            .collect();
+        assert!(
+            topologies.windows(2).all(|pair| pair[0] == pair[1]),
+            "Islands must share a brain topology so migrants can breed with the locals",
+        );
+        Self {
+            islands,
+            topology: Topology::Ring,
+            policy: MigrationPolicy::BestReplaceWorst,
+            interval: 5,
+            migration_rate: 0.1,
+            generation: 0,
+        }
+    }
+
+    pub fn random(rng: &mut dyn RngCore, num_islands: usize) -> Self {
+        Self::new((0..num_islands).map(|_| Simulation::random(rng)).collect())
+    }
+
+    pub fn from_configs(rng: &mut dyn RngCore, configs: &[Config]) -> Self {
+        Self::new(configs.iter().map(|&config| Simulation::from_config(rng, config)).collect())
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: MigrationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: usize) -> Self {
+        assert!(interval > 0);
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_migration_rate(mut self, migration_rate: f32) -> Self {
+        assert!((0.0..=1.0).contains(&migration_rate));
+        self.migration_rate = migration_rate;
+        self
+    }
+
+    pub fn islands(&self) -> &[Simulation] {
+        &self.islands
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Runs one generation on every island, migrating on the last step before
+    // evolution so migrants are ranked and bred on their full satiation
+    pub fn train(&mut self, rng: &mut dyn RngCore) -> Vec<Statistics> {
+        self.on_every_island(rng, |island, rng| {
+            while island.age < GEN_LEN {
+                island.step(rng);
+            }
+        });
+
+        self.generation += 1;
+        if self.generation % self.interval == 0 {
+            self.migrate(rng);
+        }
+
+        self.on_every_island(rng, |island, rng| island.train(rng))
+    }
+
+    // Every island gets its own ChaCha stream seeded from `rng`, so the
+    // result depends only on `rng` and never on the number of threads
+    fn on_every_island<T: Send>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        run: impl Fn(&mut Simulation, &mut dyn RngCore) -> T + Sync,
+    ) -> Vec<T> {
+        let seed: <ChaCha8Rng as SeedableRng>::Seed = rng.gen();
+
+        self.islands
+            .par_iter_mut()
+            .enumerate()
+            .map(|(index, island)| {
+                let mut rng = ChaCha8Rng::from_seed(seed);
+                rng.set_stream(index as u64);
+                run(island, &mut rng)
+            })
+            .collect()
+    }
+
+    fn migrate(&mut self, rng: &mut dyn RngCore) {
+        let num_islands = self.islands.len();
+
+        // Pick every island's emigrants before anyone arrives, so migrants
+        // don't hop more than one island per migration
+        let arrivals: Vec<Vec<Animal>> = (0..num_islands)
+            .map(|target| {
+                sources(self.topology, target, num_islands, rng)
+                    .into_iter()
+                    .flat_map(|source| {
+                        let animals = &self.islands[source].world.animals;
+                        let count = (self.migration_rate * animals.len() as f32).round() as usize;
+                        emigrants(self.policy, animals, count, rng)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (island, arrivals) in self.islands.iter_mut().zip(arrivals) {
+            let animals = &mut island.world.animals;
+            for (slot, animal) in replaced(self.policy, animals, arrivals.len(), rng).into_iter().zip(arrivals) {
+                animals[slot] = animal;
+            }
+            island.ga.forget_lineage();
+        }
+    }
+}
+
+fn sources(topology: Topology, target: usize, num_islands: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+    match topology {
+        Topology::Ring => vec![(target + num_islands - 1) % num_islands],
+        Topology::FullyConnected => (0..num_islands).filter(|&source| source != target).collect(),
+        Topology::Random => {
+            let source = rng.gen_range(0..num_islands - 1);
+            vec![if source >= target {source + 1} else {source}]
+        }
+    }
+}
+
+fn by_satiation(animals: &[Animal]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..animals.len()).collect();
+    ranked.sort_by_key(|&index| animals[index].satiation);
+    ranked
+}
+
+fn emigrants(policy: MigrationPolicy, animals: &[Animal], count: usize, rng: &mut dyn RngCore) -> Vec<Animal> {
+    let indices: Vec<usize> = match policy {
+        MigrationPolicy::BestReplaceWorst | MigrationPolicy::BestReplaceRandom => {
+            by_satiation(animals).into_iter().rev().take(count).collect()
+        }
+        MigrationPolicy::RandomReplaceRandom => {
+            rand::seq::index::sample(rng, animals.len(), count.min(animals.len())).into_vec()
+        }
+    };
+
+    indices.into_iter().map(|index| animals[index].clone()).collect()
+}
+
+// Slots in `animals` that incoming migrants overwrite
+fn replaced(policy: MigrationPolicy, animals: &[Animal], count: usize, rng: &mut dyn RngCore) -> Vec<usize> {
+    match policy {
+        MigrationPolicy::BestReplaceWorst => by_satiation(animals).into_iter().take(count).collect(),
+        MigrationPolicy::BestReplaceRandom | MigrationPolicy::RandomReplaceRandom => {
+            let mut slots: Vec<usize> = (0..animals.len()).collect();
+            slots.shuffle(rng);
+            slots.truncate(count);
+            slots
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topology_sources() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        assert_eq!(sources(Topology::Ring, 0, 4, &mut rng), vec![3]);
+        assert_eq!(sources(Topology::Ring, 2, 4, &mut rng), vec![1]);
+        assert_eq!(sources(Topology::FullyConnected, 1, 4, &mut rng), vec![0, 2, 3]);
+
+        for _ in 0..100 {
+            let source = sources(Topology::Random, 2, 4, &mut rng);
+            assert!(source.len() == 1 && source[0] != 2 && source[0] < 4);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Islands must share a brain topology")]
+    fn islands_share_a_topology() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let configs = [Config::new(9, 1, 18, 0.25, 0.01, 0.3), Config::new(5, 2, 8, 0.25, 0.01, 0.3)];
+        Archipelago::from_configs(&mut rng, &configs);
+    }
+
+    #[test]
+    fn islands_get_their_own_reproducible_streams() {
+        let draw = || {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut archipelago = Archipelago::random(&mut rng, 3);
+            archipelago.on_every_island(&mut rng, |_, rng| rng.next_u64())
+        };
+
+        let draws = draw();
+        assert_eq!(draws, draw());
+        assert!(draws[0] != draws[1] && draws[1] != draws[2]);
+    }
+
+    #[test]
+    fn best_migrants_replace_worst() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut archipelago = Archipelago::random(&mut rng, 2)
+            .with_migration_rate(0.05);
+
+        // Two best animals on island 0, tagged by satiation
+        archipelago.islands[0].world.animals[7].satiation = 100;
+        archipelago.islands[0].world.animals[3].satiation = 50;
+        for (index, animal) in archipelago.islands[1].world.animals.iter_mut().enumerate() {
+            animal.satiation = index + 1;
+        }
+
+        archipelago.migrate(&mut rng);
+
+        let satiation: Vec<usize> = archipelago.islands[1].world.animals
+            .iter()
+            .map(|animal| animal.satiation)
+            .collect();
+        assert_eq!(&satiation[..3], &[100, 50, 3]);
+    }
+}
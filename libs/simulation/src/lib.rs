@@ -4,6 +4,11 @@ mod world;
 mod eye;
 mod animal_individual;
 mod brain;
+mod island;
+mod observer;
+mod behavior;
+mod coevolution;
+mod stagnation;
 
 use lib_neural_network as nn;
 use nn::matrix_network as mn;
@@ -11,20 +16,112 @@ use lib_genetic_algorithm as ga;
 use lib_config::Config;
 use nalgebra as na;
 
-use ga::{mutation_method, selection_method, crossover_method, chromosome::Chromosome};
-use rand::{RngCore, Rng};
+use ga::{mutation_method, selection_method, crossover_method, fitness_transform, chromosome::Chromosome};
+use ga::gene_layout::{GeneKind, GeneLayout, GeneSegment};
+use ga::termination::{Progress, Termination, TerminationCriterion};
+use rand::{RngCore, Rng, SeedableRng};
 use std::f32::consts::FRAC_PI_2;
-pub use self::{animal::*, food::*, world::*, eye::*, animal_individual::*, brain::*};
+use std::time::Duration;
+pub use self::{animal::*, food::*, world::*, eye::*, animal_individual::*, brain::*, island::*, observer::*, behavior::*, coevolution::*, stagnation::*};
 
 const SPEED_MIN: f32 = 0.001;
 const SPEED_MAX: f32 = 0.005;
 const SPEED_ACCEL: f32 = 0.2;
 const ROTATION_ACCEL: f32 = FRAC_PI_2;
 const GEN_LEN: usize = 2500;
+const HALL_OF_FAME_SIZE: usize = 10;
+const NUM_ANIMALS: usize = 40;
+const NUM_FOODS: usize = 60;
+// How close an animal has to get to food to eat it
+const EAT_DISTANCE: f32 = 0.007;
+
+/// How parents are picked. Roulette wheel by default; early generations
+/// often have nobody eating at all, so it falls back to picking uniformly
+/// rather than failing on an all-zero wheel. Only tournament and rank
+/// selection have a pressure to schedule.
+pub enum Selection {
+    RouletteWheel(selection_method::Scaled<selection_method::RouletteWheelSelection, fitness_transform::UniformFallback>),
+    Tournament(selection_method::TournamentSelection),
+    Rank(selection_method::RankSelection),
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self::RouletteWheel(selection_method::Scaled::new(
+            selection_method::RouletteWheelSelection,
+            fitness_transform::UniformFallback,
+        ))
+    }
+}
+
+impl selection_method::SelectionMethod for Selection {
+    fn select_index(&self, rng: &mut dyn RngCore, fitness: &[f32]) -> Result<usize, selection_method::SelectionError> {
+        match self {
+            Self::RouletteWheel(selection) => selection.select_index(rng, fitness),
+            Self::Tournament(selection) => selection.select_index(rng, fitness),
+            Self::Rank(selection) => selection.select_index(rng, fitness),
+        }
+    }
+
+    fn select_indices(
+        &self,
+        rng: &mut dyn RngCore,
+        fitness: &[f32],
+        count: usize,
+    ) -> Result<Vec<usize>, selection_method::SelectionError> {
+        match self {
+            Self::RouletteWheel(selection) => selection.select_indices(rng, fitness, count),
+            Self::Tournament(selection) => selection.select_indices(rng, fitness, count),
+            Self::Rank(selection) => selection.select_indices(rng, fitness, count),
+        }
+    }
+
+    fn set_pressure(&mut self, pressure: f32) -> bool {
+        match self {
+            Self::RouletteWheel(selection) => selection.set_pressure(pressure),
+            Self::Tournament(selection) => selection.set_pressure(pressure),
+            Self::Rank(selection) => selection.set_pressure(pressure),
+        }
+    }
+}
+
+type Penalty = dyn Fn(&Chromosome) -> f32 + Send + Sync;
+
+// What breeds the next generation; exactly one per simulation
+enum Engine {
+    Genetic,
+    CmaEs(Box<ga::cma_es::CmaEs>),
+    DifferentialEvolution(ga::differential_evolution::DifferentialEvolution),
+    MapElites(BehaviorDescriptor, ga::map_elites::MapElites),
+    // With the survivors of the last generation, which compete with the
+    // children they bred
+    Nsga2(Box<ga::nsga2::Nsga2>, Vec<AnimalIndividual>),
+}
 
 pub struct Simulation {
     world: World,
-    ga: ga::GeneticAlgorithm<selection_method::RouletteWheelSelection>,
+    ga: ga::GeneticAlgorithm<Selection>,
+    hall_of_fame: ga::hall_of_fame::HallOfFame,
+    // Objective vectors of the last finished generation's Pareto front
+    pareto_front: Vec<Vec<f32>>,
+    speciation: Option<ga::speciation::Speciation>,
+    novelty: Option<NoveltySearch>,
+    // Also applied to what engines other than `ga` breed
+    repair: Option<ga::bounds::BoundsRepair>,
+    penalty: Option<Box<Penalty>>,
+    // `ga` unless replaced; `ga` still notifies its observers either way
+    engine: Engine,
+    // Breed on rayon's thread pool. Reproducible for a given seed whatever
+    // the thread count, but not the same run as breeding sequentially.
+    parallel: bool,
+    stagnation: Option<StagnationPolicy>,
+    // What new animals are built from, the i-th one from `configs[i % len]`;
+    // `Animal::random` when empty
+    configs: Vec<Config>,
+    // What the last call to `evolve` did
+    report: ga::evolution_report::EvolutionReport,
+    observers: Vec<Box<dyn SimulationObserver>>,
+    generation: usize,
     age: usize,
 }
 
@@ -33,7 +130,7 @@ impl Simulation {
     pub fn random(rng: &mut dyn RngCore) -> Self {
         let world = World::random(rng);
         let ga = ga::GeneticAlgorithm::new(
-            selection_method::RouletteWheelSelection,
+            Selection::default(),
             crossover_method::UniformCrossover,
             mutation_method::GaussianMutation::new(0.01, 0.03),
         );
@@ -41,6 +138,19 @@ impl Simulation {
         Self {
             world,
             ga,
+            hall_of_fame: ga::hall_of_fame::HallOfFame::new(HALL_OF_FAME_SIZE),
+            pareto_front: Vec::new(),
+            speciation: None,
+            novelty: None,
+            repair: None,
+            penalty: None,
+            engine: Engine::Genetic,
+            parallel: false,
+            stagnation: None,
+            configs: Vec::new(),
+            report: Default::default(),
+            observers: Vec::new(),
+            generation: 0,
             age: 0,
         }
     }
@@ -49,21 +159,289 @@ impl Simulation {
         &self.world
     }
 
+    // Select on fitness shared within species of brains closer than
+    // `threshold`; only for the genetic algorithm
+    pub fn with_speciation(mut self, threshold: f32) -> Self {
+        assert!(matches!(self.engine, Engine::Genetic), "Speciation only works with the genetic algorithm");
+        self.speciation = Some(ga::speciation::Speciation::new(threshold));
+        self
+    }
+
+    // Generational by default; see `ga::replacement` for steady-state and
+    // (mu+lambda)/(mu,lambda) replacement
+    pub fn with_replacement(mut self, replacement: impl ga::replacement::ReplacementStrategy + 'static) -> Self {
+        self.ga = self.ga.with_replacement(replacement);
+        self
+    }
+
+    // Evolve on a blend of satiation and novelty, to escape behaviours that
+    // look good early on but lead nowhere, e.g. spinning in place.
+    // Differential evolution still sees raw satiation: it keeps each target's
+    // fitness across generations, and novelty scores from back then go stale
+    pub fn with_novelty_search(mut self, novelty: NoveltySearch) -> Self {
+        self.novelty = Some(novelty);
+        self
+    }
+
+    pub fn novelty_search(&self) -> Option<&NoveltySearch> {
+        self.novelty.as_ref()
+    }
+
+    // Breed the GA's children on all cores. A seed gives the same results
+    // on any number of threads, though not the ones a sequential run would.
+    pub fn with_parallel_evolution(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    // React to average satiation flatlining, see `StagnationPolicy`
+    pub fn with_stagnation_policy(mut self, policy: StagnationPolicy) -> Self {
+        assert!(
+            matches!(self.engine, Engine::Genetic) || !policy.boosts_mutation(),
+            "Mutation boosts only work with the genetic algorithm",
+        );
+        self.stagnation = Some(policy);
+        self
+    }
+
+    pub fn stagnation_policy(&self) -> Option<&StagnationPolicy> {
+        self.stagnation.as_ref()
+    }
+
+    // Evolve with MAP-Elites: `archive` keeps the best brain per cell of a
+    // grid over the behaviors `descriptor` yields, and every generation is
+    // bred from random elites
+    pub fn with_map_elites(mut self, descriptor: BehaviorDescriptor, archive: ga::map_elites::MapElites) -> Self {
+        self.replace_engine(Engine::MapElites(descriptor, archive));
+        self
+    }
+
+    pub fn map_elites(&self) -> Option<&ga::map_elites::MapElites> {
+        match &self.engine {
+            Engine::MapElites(_, archive) => Some(archive),
+            _ => None,
+        }
+    }
+
+    // Evolve on food eaten, distance travelled and brain size at once: each
+    // generation competes with its parents, and the best fronts survive
+    pub fn with_nsga2(mut self, nsga2: ga::nsga2::Nsga2) -> Self {
+        self.replace_engine(Engine::Nsga2(Box::new(nsga2), Vec::new()));
+        self
+    }
+
+    // Evolve brains with CMA-ES instead of the genetic algorithm. The search
+    // starts at the average brain with step size `sigma`, and the current
+    // animals are replaced by the first CMA-ES samples.
+    pub fn with_cma_es(mut self, rng: &mut dyn RngCore, sigma: f32) -> Self {
+        let chromosomes: Vec<Chromosome> = self.world.animals.iter().map(Animal::as_chromosome).collect();
+        assert!(!chromosomes.is_empty(), "CMA-ES needs at least one animal to start from");
+        self.assert_single_topology();
+        let mut mean: Chromosome = (0..chromosomes[0].len())
+            .map(|gene| chromosomes.iter().map(|chromosome| chromosome[gene]).sum::<f32>() / chromosomes.len() as f32)
+            .collect();
+        mean.inherit_from(&chromosomes[0], &chromosomes[0]);
+
+        let cma_es = ga::cma_es::CmaEs::with_population_size(&mean, sigma, self.world.animals.len());
+        self.world.animals = cma_es
+            .ask(rng)
+            .into_iter()
+            .map(|chromosome| Animal::from_chromosome(chromosome, &self.configs, rng))
+            .collect();
+        self.replace_engine(Engine::CmaEs(Box::new(cma_es)));
+        self
+    }
+
+    fn replace_engine(&mut self, engine: Engine) {
+        assert!(matches!(self.engine, Engine::Genetic), "Simulation already evolves with another engine");
+        assert!(self.speciation.is_none(), "Speciation only works with the genetic algorithm");
+        assert!(
+            !self.stagnation.as_ref().is_some_and(StagnationPolicy::boosts_mutation),
+            "Mutation boosts only work with the genetic algorithm",
+        );
+        self.assert_single_topology();
+        self.engine = engine;
+    }
+
+    fn assert_single_topology(&self) {
+        let lengths: Vec<usize> = self.world.animals.iter().map(|animal| animal.as_chromosome().len()).collect();
+        assert!(
+            lengths.windows(2).all(|pair| pair[0] == pair[1]),
+            "Only the genetic algorithm can breed brains of different topologies",
+        );
+    }
+
+    // Keep brain weights within bounds, e.g. the -1.0..=1.0 they start in,
+    // whichever engine breeds them
+    pub fn with_repair(mut self, repair: ga::bounds::BoundsRepair) -> Self {
+        self.ga = self.ga.with_repair(repair.clone());
+        self.repair = Some(repair);
+        self
+    }
+
+    // Evolve on satiation minus `penalty(brain)`, floored at zero so roulette
+    // selection keeps working; e.g. `BoundsRepair::violation` to punish
+    // weights out of bounds softly. NSGA-II ranks on its objectives and ignores it.
+    pub fn with_penalty(mut self, penalty: impl Fn(&Chromosome) -> f32 + Send + Sync + 'static) -> Self {
+        self.penalty = Some(Box::new(penalty));
+        self
+    }
+
+    // Refine every newborn brain with a few rounds of `method` before it
+    // joins the world, scoring candidates on the food one animal eats alone
+    // in `episode_steps` steps of a fixed practice world
+    pub fn with_local_search(
+        mut self,
+        method: ga::memetic::LocalSearchMethod,
+        iterations: usize,
+        inheritance: ga::memetic::Inheritance,
+        episode_steps: usize,
+    ) -> Self {
+        let configs = self.configs.clone();
+        let local_search = ga::memetic::LocalSearch::new(method, iterations, inheritance, move |chromosome: &Chromosome| {
+            practice_episode(chromosome, &configs, episode_steps)
+        });
+        self.ga = self.ga.with_local_search(local_search);
+        self
+    }
+
+    // Fill in the diversity and mutated gene figures of `evolution_report`,
+    // at O(N²·L) per generation
+    pub fn with_gene_statistics(mut self) -> Self {
+        self.ga = self.ga.with_gene_statistics();
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.ga = self.ga.with_selection_method(selection);
+        self
+    }
+
+    // Adjust mutation or selection pressure every generation; the values
+    // used show up in `evolution_report`. Pressure schedules need tournament
+    // or rank selection, and are skipped with the roulette wheel.
+    pub fn with_schedule(mut self, parameter: ga::schedule::Parameter, schedule: impl ga::schedule::Schedule + 'static) -> Self {
+        self.ga = self.ga.with_schedule(parameter, schedule);
+        self
+    }
+
+    // Per-species statistics for the last finished generation; empty unless
+    // speciation is enabled
+    pub fn species_stats(&self) -> &[ga::speciation::SpeciesStats] {
+        self.speciation
+            .as_ref()
+            .map_or(&[], |speciation| speciation.stats())
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Observers are notified in the order they were added
+    pub fn add_observer(&mut self, observer: impl SimulationObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn add_evolution_observer(&mut self, observer: impl ga::observer::EvolutionObserver + 'static) {
+        self.ga.add_observer(observer);
+    }
+
+    pub fn hall_of_fame(&self) -> &ga::hall_of_fame::HallOfFame {
+        &self.hall_of_fame
+    }
+
+    // Fitness and parent usage figures for the last finished generation,
+    // plus diversity and mutation with `with_gene_statistics`
+    pub fn evolution_report(&self) -> &ga::evolution_report::EvolutionReport {
+        &self.report
+    }
+
+    // Food eaten, negated distance travelled and negated brain size of the
+    // animals no other animal beat on all three, as of the last generation
+    pub fn pareto_front(&self) -> &[Vec<f32>] {
+        &self.pareto_front
+    }
+
+    // Swap the `count` hungriest animals for the best brains ever recorded
+    pub fn reinject_hall_of_fame(&mut self, rng: &mut dyn RngCore, count: usize) {
+        let mut population: Vec<_> = self.world.animals.iter().map(AnimalIndividual::from_animal).collect();
+        self.hall_of_fame.reinject(&mut population, count);
+
+        // Animals whose brain didn't change keep their position and satiation
+        for (animal, individual) in self.world.animals.iter_mut().zip(population) {
+            if individual.chromosome != animal.as_chromosome() {
+                *animal = individual.into_animal(&self.configs, rng);
+            }
+        }
+        self.ga.forget_lineage();
+    }
+
     pub fn from_config(rng: &mut dyn RngCore, config: Config) -> Self {
         let world = World::from_config(rng, config);
         let ga = ga::GeneticAlgorithm::new(
-            selection_method::RouletteWheelSelection,
+            Selection::default(),
             crossover_method::UniformCrossover,
-            mutation_method::GaussianMutation::new(config.mutation_chance, config.mutation_coef)
+            mutation_method::GaussianMutation::from_config(config)
         );
 
         Self {
             world,
             ga,
+            hall_of_fame: ga::hall_of_fame::HallOfFame::new(HALL_OF_FAME_SIZE),
+            pareto_front: Vec::new(),
+            speciation: None,
+            novelty: None,
+            repair: None,
+            penalty: None,
+            engine: match ga::differential_evolution::DifferentialEvolution::from_config(config) {
+                Some(de) => Engine::DifferentialEvolution(de),
+                None => Engine::Genetic,
+            },
+            parallel: false,
+            stagnation: None,
+            configs: vec![config],
+            report: Default::default(),
+            observers: Vec::new(),
+            generation: 0,
             age: 0
         }
     }
 
+    // One population of brains built from several configs, which may differ
+    // in eye cells and hidden layers. Crossover aligns genes by their place
+    // in the network so different topologies can breed; mutation follows the
+    // first config. Only the genetic algorithm can breed mixed topologies.
+    pub fn from_configs(rng: &mut dyn RngCore, configs: &[Config]) -> Self {
+        assert!(
+            configs.iter().all(|config| config.engine == lib_config::Engine::Genetic),
+            "Only the genetic algorithm can breed brains of different topologies",
+        );
+        let ga = ga::GeneticAlgorithm::new(
+            Selection::default(),
+            crossover_method::AlignedCrossover,
+            mutation_method::GaussianMutation::from_config(configs[0])
+        );
+
+        Self {
+            world: World::from_configs(rng, configs),
+            ga,
+            hall_of_fame: ga::hall_of_fame::HallOfFame::new(HALL_OF_FAME_SIZE),
+            pareto_front: Vec::new(),
+            speciation: None,
+            novelty: None,
+            repair: None,
+            penalty: None,
+            engine: Engine::Genetic,
+            parallel: false,
+            stagnation: None,
+            configs: configs.to_vec(),
+            report: Default::default(),
+            observers: Vec::new(),
+            generation: 0,
+            age: 0,
+        }
+    }
+
     // This might lowkey break everything lets see
     pub fn set_world(&mut self, world: World) {
         self.world = world;
@@ -71,15 +449,31 @@ impl Simulation {
 
     // Perform a single step forward
     pub fn step(&mut self, rng: &mut dyn RngCore) -> Option<Statistics> {
+        if self.age == 0 {
+            for observer in &mut self.observers {
+                observer.on_generation_start(self.generation, &self.world);
+            }
+        }
+
         self.process_collisions(rng);
         self.process_brains();
         self.process_movements();
 
         self.age += 1;
+        for observer in &mut self.observers {
+            observer.on_step(self.age, &self.world);
+        }
 
         if self.age > GEN_LEN {
             let stats = Statistics::find_stats(&self.world.animals);
             self.evolve(rng);
+            self.respond_to_stagnation(rng, &stats);
+
+            for observer in &mut self.observers {
+                observer.on_generation_end(self.generation, &stats, &self.report);
+            }
+            self.generation += 1;
+
             Some(stats)
             
         } else {
@@ -96,22 +490,112 @@ impl Simulation {
         }
     }
 
-    pub fn optimize_from_config(&mut self, rng: &mut dyn RngCore, config: Config) -> Vec<Statistics> {
+    // Trains generation after generation until `criterion` fires, with max
+    // satiation as best fitness and average satiation as mean fitness. There
+    // is no clock on wasm32, where elapsed time stays zero and a `TimeLimit`
+    // never fires.
+    pub fn train_until(
+        &mut self,
+        rng: &mut dyn RngCore,
+        criterion: &mut dyn TerminationCriterion,
+    ) -> (Vec<Statistics>, Termination) {
+        let elapsed = stopwatch();
+        let mut history = Vec::new();
+
+        loop {
+            let stats = self.train(rng);
+            history.push(stats);
+
+            let progress = Progress {
+                generation: history.len(),
+                best_fitness: stats.max as f32,
+                mean_fitness: stats.avg,
+                elapsed: elapsed(),
+            };
+
+            if let Some(reason) = criterion.check(&progress) {
+                return (history, reason);
+            }
+        }
+    }
+
+    pub fn optimize_from_config(&mut self, _rng: &mut dyn RngCore, _config: Config) -> Vec<Statistics> {
         todo!()
     }
 
     fn evolve(&mut self, rng: &mut dyn RngCore) {
         self.age = 0;
 
-        let current_population: Vec<_> = self.world
+        let mut current_population: Vec<_> = self.world
             .animals
             .iter()
-            .map(|animal| AnimalIndividual::from_animal(animal))
+            .map(AnimalIndividual::from_animal)
+            .collect();
+        self.hall_of_fame.record(&current_population);
+        self.pareto_front = ga::nsga2::pareto_front(&current_population)
+            .into_iter()
+            .map(|individual| individual.objectives.clone())
             .collect();
-        let evolved_population = self.ga.evolve(rng, &current_population);
+        // After recording, so the hall of fame keeps ranking on satiation
+        if let Some(novelty) = &mut self.novelty {
+            if !matches!(self.engine, Engine::DifferentialEvolution(_)) {
+                novelty.apply(&self.world.animals, &mut current_population);
+            }
+        }
+        if let Some(penalty) = &self.penalty {
+            for individual in &mut current_population {
+                individual.fitness = (individual.fitness - penalty(&individual.chromosome)).max(0.0);
+            }
+        }
+        let evolved_population = match &mut self.engine {
+            Engine::Genetic => {
+                let fitness: Vec<f32> = match &mut self.speciation {
+                    Some(speciation) => speciation.shared_fitness(&current_population),
+                    None => current_population.iter().map(|individual| individual.fitness).collect(),
+                };
+                let (evolved_population, report) = if self.parallel {
+                    self.ga.evolve_parallel_with_fitness(rng, &current_population, &fitness)
+                } else {
+                    self.ga.evolve_with_fitness(rng, &current_population, &fitness)
+                }
+                .expect("World has no animals");
+                self.report = report;
+                evolved_population
+            }
+            engine => {
+                let mut evolved_population = match engine {
+                    Engine::CmaEs(cma_es) => cma_es.evolve(rng, &current_population),
+                    Engine::DifferentialEvolution(de) => de.evolve(rng, &current_population),
+                    Engine::MapElites(descriptor, archive) => {
+                        let behaviors: Vec<_> = self.world.animals.iter().map(|animal| animal.behavior(*descriptor)).collect();
+                        archive.evolve(rng, &current_population, &behaviors)
+                    }
+                    Engine::Nsga2(nsga2, parents) => {
+                        let mut pool = std::mem::take(parents);
+                        pool.extend(current_population.iter().cloned());
+                        *parents = nsga2
+                            .survivors(&pool, current_population.len())
+                            .into_iter()
+                            .cloned()
+                            .collect();
+                        nsga2.evolve(rng, parents).expect("World has no animals")
+                    }
+                    Engine::Genetic => unreachable!(),
+                };
+                if let Some(repair) = &self.repair {
+                    for individual in &mut evolved_population {
+                        repair.apply(&mut individual.chromosome);
+                    }
+                }
+                let fitness: Vec<f32> = current_population.iter().map(|individual| individual.fitness).collect();
+                self.report = ga::evolution_report::EvolutionReport::of_population(&current_population);
+                self.ga.observe_generation(&fitness, &self.report);
+                evolved_population
+            }
+        };
         self.world.animals = evolved_population
                 .into_iter()
-                .map(|individual| individual.into_animal(rng))
+                .map(|individual| individual.into_animal(&self.configs, rng))
                 .collect();
 
         for food in &mut self.world.foods {
@@ -124,9 +608,13 @@ impl Simulation {
             for food in &mut self.world.foods {
                 let distance = na::distance(&animal.position, &food.position);
 
-                if distance <= 0.007 {
+                if distance <= EAT_DISTANCE {
                     animal.satiation += 1;
                     food.position = rng.gen();
+
+                    for observer in &mut self.observers {
+                        observer.on_food_eaten(animal, food);
+                    }
                 }
             }
         }
@@ -134,31 +622,53 @@ impl Simulation {
 
     fn process_brains(&mut self) {
         for animal in &mut self.world.animals {
-            let vision = animal.eye.process_vision(
-                animal.position, 
-                animal.rotation, 
-                &self.world.foods
-        );
-    
-        let response = animal.brain.nn.forward(vision);
-
-        let speed = response[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
-        let rotation = response[1].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
-
-        animal.speed = (animal.speed + speed).clamp(SPEED_MIN, SPEED_MAX);
-        animal.rotation = na::Rotation2::new(animal.rotation.angle() + rotation);
-
+            animal.think(&self.world.foods);
         }
     }
 
     fn process_movements(&mut self) {
         for animal in &mut self.world.animals {
-            animal.position += animal.rotation * na::Vector2::new(0.0, animal.speed);
+            animal.advance();
+        }
+    }
+}
 
-            animal.position.x = na::wrap(animal.position.x, 0.0, 1.0);
-            animal.position.y = na::wrap(animal.position.y, 0.0, 1.0);
+// Food eaten by one animal with `chromosome`'s brain, alone for `steps`
+// steps in a world that's the same every time, so candidates compare fairly
+fn practice_episode(chromosome: &Chromosome, configs: &[Config], steps: usize) -> f32 {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let foods = (0..NUM_FOODS).map(|_| Food::random(&mut rng)).collect();
+    let animal = Animal::from_chromosome(chromosome.clone(), configs, &mut rng);
+    let mut world = World {
+        animals: vec![animal],
+        foods,
+    };
+
+    for _ in 0..steps {
+        let animal = &mut world.animals[0];
+        for food in &mut world.foods {
+            if na::distance(&animal.position, &food.position) <= EAT_DISTANCE {
+                animal.satiation += 1;
+                food.position = rng.gen();
+            }
         }
+        animal.think(&world.foods);
+        animal.advance();
     }
+
+    world.animals[0].satiation as f32
+}
+
+// Time since the call; `Instant::now` panics on wasm32-unknown-unknown
+#[cfg(not(target_arch = "wasm32"))]
+fn stopwatch() -> impl Fn() -> Duration {
+    let start = std::time::Instant::now();
+    move || start.elapsed()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn stopwatch() -> impl Fn() -> Duration {
+    || Duration::ZERO
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -171,13 +681,13 @@ pub struct Statistics {
 impl Statistics {
     pub fn find_stats(population: &[Animal]) -> Self {
         let min = population
-            .into_iter()
+            .iter()
             .min_by(|x, y| x.satiation.cmp(&y.satiation))
             .unwrap()
             .satiation;
 
         let max = population
-            .into_iter()
+            .iter()
             .max_by(|x, y| x.satiation.cmp(&y.satiation))
             .unwrap()
             .satiation;
@@ -219,4 +729,282 @@ impl Default for Statistics {
             max: 0
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn cma_es_replaces_the_genetic_algorithm() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(&mut rng).with_cma_es(&mut rng, 0.1);
+        let num_animals = simulation.world().animals().len();
+
+        for (index, animal) in simulation.world.animals.iter_mut().enumerate() {
+            animal.satiation = index;
+        }
+        let before = simulation.world.animals[0].as_chromosome();
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.world().animals().len(), num_animals);
+        assert_eq!(simulation.evolution_report().fitness.max, (num_animals - 1) as f32);
+        assert_eq!(simulation.evolution_report().mutated_genes, 0);
+        assert_eq!(simulation.world.animals[0].as_chromosome().len(), before.len());
+        assert_ne!(simulation.world.animals[0].as_chromosome(), before);
+    }
+
+    struct GenerationCounter(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl ga::observer::EvolutionObserver for GenerationCounter {
+        fn on_generation_end(&mut self, generation: usize, _report: &ga::evolution_report::EvolutionReport) {
+            self.0.store(generation + 1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn other_engines_notify_evolution_observers() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let generations = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut simulation = Simulation::random(&mut rng).with_cma_es(&mut rng, 0.1);
+        simulation.add_evolution_observer(GenerationCounter(generations.clone()));
+
+        simulation.evolve(&mut rng);
+        simulation.evolve(&mut rng);
+
+        assert_eq!(generations.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "already evolves with another engine")]
+    fn engines_are_mutually_exclusive() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let archive = ga::map_elites::MapElites::new(
+            vec![ga::map_elites::BehaviorAxis::new(0.0, 1.0, 5)],
+            crossover_method::UniformCrossover,
+            mutation_method::GaussianMutation::new(0.01, 0.03),
+        );
+        Simulation::random(&mut rng)
+            .with_cma_es(&mut rng, 0.1)
+            .with_map_elites(BehaviorDescriptor::FinalPosition, archive);
+    }
+
+    #[test]
+    fn other_engines_respect_repair_and_penalty() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let repair = ga::bounds::BoundsRepair::new(ga::bounds::RepairMethod::Clamp).with_default_bounds(-0.1..=0.1);
+        let mut simulation = Simulation::random(&mut rng)
+            .with_cma_es(&mut rng, 1.0)
+            .with_repair(repair)
+            .with_penalty(|_| 1_000.0);
+        for animal in &mut simulation.world.animals {
+            animal.satiation = 10;
+        }
+
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.evolution_report().fitness.max, 0.0);
+        for animal in &simulation.world.animals {
+            assert!(animal.as_chromosome().iter().all(|weight| (-0.1..=0.1).contains(weight)));
+        }
+    }
+
+    #[test]
+    fn penalty_punishes_weights_out_of_bounds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let bounds = ga::bounds::BoundsRepair::new(ga::bounds::RepairMethod::Clamp).with_default_bounds(-1.0..=1.0);
+        let mut simulation = Simulation::random(&mut rng)
+            .with_penalty(move |chromosome| bounds.violation(chromosome));
+
+        let mut chromosome = simulation.world.animals[0].as_chromosome();
+        chromosome.as_mut_slice()[0] = 4.0;
+        simulation.world.animals[0] = Animal::from_chromosome(chromosome, &[], &mut rng);
+        for animal in &mut simulation.world.animals {
+            animal.satiation = 10;
+        }
+
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.evolution_report().fitness.max, 10.0);
+        assert_eq!(simulation.evolution_report().fitness.min, 7.0);
+    }
+
+    #[test]
+    fn selection_pressure_can_be_scheduled() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let pressure = || ga::schedule::LinearDecay::new(4.0, 2.0, 10);
+
+        let mut roulette = Simulation::random(&mut rng)
+            .with_schedule(ga::schedule::Parameter::SelectionPressure, pressure());
+        roulette.evolve(&mut rng);
+        assert!(roulette.evolution_report().parameters.is_empty());
+
+        let mut tournament = Simulation::random(&mut rng)
+            .with_selection(Selection::Tournament(selection_method::TournamentSelection::new(2)))
+            .with_schedule(ga::schedule::Parameter::SelectionPressure, pressure());
+        tournament.evolve(&mut rng);
+        assert_eq!(
+            tournament.evolution_report().parameters,
+            vec![(ga::schedule::Parameter::SelectionPressure, 4.0)],
+        );
+    }
+
+    #[test]
+    fn config_selects_differential_evolution() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut config = Config::new(9, 1, 18, 0.25, 0.01, 0.3);
+        config.engine = lib_config::Engine::DifferentialEvolution {
+            strategy: lib_config::DeStrategy::Rand1Bin,
+            control: lib_config::DeControl::SelfAdaptive,
+        };
+        let mut simulation = Simulation::from_config(&mut rng, config);
+        assert!(matches!(simulation.engine, Engine::DifferentialEvolution(_)));
+
+        let num_animals = simulation.world().animals().len();
+        simulation.evolve(&mut rng);
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.world().animals().len(), num_animals);
+        let Engine::DifferentialEvolution(de) = &simulation.engine else {
+            unreachable!()
+        };
+        assert!(de.best().is_some());
+    }
+
+    #[test]
+    fn differential_evolution_ignores_novelty() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut config = Config::new(9, 1, 18, 0.25, 0.01, 0.3);
+        config.engine = lib_config::Engine::DifferentialEvolution {
+            strategy: lib_config::DeStrategy::Rand1Bin,
+            control: lib_config::DeControl::SelfAdaptive,
+        };
+        let novelty = NoveltySearch::new(
+            BehaviorDescriptor::FinalPosition,
+            ga::novelty::NoveltyArchive::new(3, 0.0, 10),
+            1.0,
+        );
+        let mut simulation = Simulation::from_config(&mut rng, config).with_novelty_search(novelty);
+        for (index, animal) in simulation.world.animals.iter_mut().enumerate() {
+            animal.satiation = index;
+        }
+        let num_animals = simulation.world().animals().len();
+
+        simulation.evolve(&mut rng);
+
+        let Engine::DifferentialEvolution(de) = &simulation.engine else {
+            unreachable!()
+        };
+        assert_eq!(de.best().unwrap().1, (num_animals - 1) as f32);
+    }
+
+    #[test]
+    fn nsga2_keeps_dominant_parents() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let nsga2 = ga::nsga2::Nsga2::new(
+            crossover_method::UniformCrossover,
+            mutation_method::GaussianMutation::new(0.01, 0.03),
+        );
+        let mut simulation = Simulation::random(&mut rng).with_nsga2(nsga2);
+        let num_animals = simulation.world().animals().len();
+
+        simulation.world.animals[0].satiation = 1_000;
+        let champion = simulation.world.animals[0].as_chromosome();
+        simulation.evolve(&mut rng);
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.world().animals().len(), num_animals);
+        let Engine::Nsga2(_, parents) = &simulation.engine else {
+            unreachable!()
+        };
+        assert_eq!(parents.len(), num_animals);
+        assert!(parents.iter().any(|parent| parent.chromosome == champion));
+    }
+
+    #[test]
+    fn map_elites_archives_behaviors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let archive = ga::map_elites::MapElites::new(
+            vec![ga::map_elites::BehaviorAxis::new(0.0, 1.0, 5), ga::map_elites::BehaviorAxis::new(0.0, 1.0, 5)],
+            crossover_method::UniformCrossover,
+            mutation_method::GaussianMutation::new(0.01, 0.03),
+        );
+        let mut simulation = Simulation::random(&mut rng)
+            .with_map_elites(BehaviorDescriptor::FinalPosition, archive);
+        let num_animals = simulation.world().animals().len();
+
+        simulation.evolve(&mut rng);
+
+        let archive = simulation.map_elites().unwrap();
+        assert!(archive.len() > 1);
+        assert_eq!(simulation.world().animals().len(), num_animals);
+    }
+
+    #[test]
+    fn local_search_practices_in_a_fixed_world() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(&mut rng).with_local_search(
+            ga::memetic::LocalSearchMethod::RandomPerturbation { sigma: 0.1 },
+            2,
+            ga::memetic::Inheritance::Lamarckian,
+            100,
+        );
+        let num_animals = simulation.world().animals().len();
+
+        simulation.evolve(&mut rng);
+
+        assert_eq!(simulation.world().animals().len(), num_animals);
+        let brain = simulation.world.animals[0].as_chromosome();
+        assert_eq!(practice_episode(&brain, &[], 100), practice_episode(&brain, &[], 100));
+    }
+
+    #[test]
+    fn mixed_topologies_evolve_together() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let configs = [Config::new(9, 1, 18, 0.25, 0.01, 0.3), Config::new(5, 2, 8, 0.25, 0.01, 0.3)];
+        let mut simulation = Simulation::from_configs(&mut rng, &configs);
+
+        let lengths = |simulation: &Simulation| -> Vec<usize> {
+            let mut lengths: Vec<usize> = simulation.world.animals.iter().map(|animal| animal.as_chromosome().len()).collect();
+            lengths.sort_unstable();
+            lengths.dedup();
+            lengths
+        };
+        assert_eq!(lengths(&simulation).len(), 2);
+
+        for (index, animal) in simulation.world.animals.iter_mut().enumerate() {
+            animal.satiation = index % 3;
+        }
+        simulation.evolve(&mut rng);
+        simulation.step(&mut rng);
+
+        assert_eq!(lengths(&simulation).len(), 2);
+        assert!(simulation.world.animals.iter().all(|animal| animal.eye.cells() == animal.as_chromosome().layout().unwrap().layer_sizes()[0]));
+    }
+
+    #[test]
+    fn newborns_keep_their_config_field_of_view() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut configs = [Config::new(9, 1, 18, 0.4, 0.01, 0.3), Config::new(5, 2, 8, 0.1, 0.01, 0.3)];
+        configs[1].fov_angle = 1.0;
+        let mut simulation = Simulation::from_configs(&mut rng, &configs);
+
+        simulation.evolve(&mut rng);
+
+        for animal in &simulation.world.animals {
+            let config = if animal.eye.cells() == 9 {configs[0]} else {configs[1]};
+            assert_eq!(animal.eye.fov_range(), config.fov_range);
+            assert_eq!(animal.eye.fov_angle(), config.fov_angle);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the genetic algorithm can breed brains of different topologies")]
+    fn mixed_topologies_reject_other_engines() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let configs = [Config::new(9, 1, 18, 0.25, 0.01, 0.3), Config::new(5, 2, 8, 0.25, 0.01, 0.3)];
+        Simulation::from_configs(&mut rng, &configs).with_cma_es(&mut rng, 0.1);
+    }
+}
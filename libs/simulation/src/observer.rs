@@ -0,0 +1,79 @@
+use crate::*;
+
+/// Hooks into `Simulation::step` for loggers, checkpoint writers and
+/// visualizers. Every method defaults to doing nothing; use
+/// `Simulation::add_evolution_observer` to also watch selection.
+pub trait SimulationObserver: Send {
+    // Before the first step of every generation
+    fn on_generation_start(&mut self, _generation: usize, _world: &World) {}
+
+    // After the animals have moved; `age` counts steps within the generation
+    fn on_step(&mut self, _age: usize, _world: &World) {}
+
+    // `food` has already been moved to its new position
+    fn on_food_eaten(&mut self, _animal: &Animal, _food: &Food) {}
+
+    // After a `StagnationPolicy` has reacted to the finished generation
+    fn on_stagnation(&mut self, _event: &StagnationEvent) {}
+
+    // `stats` describe the finished generation, `report` how it was evolved
+    fn on_generation_end(
+        &mut self,
+        _generation: usize,
+        _stats: &Statistics,
+        _report: &ga::evolution_report::EvolutionReport,
+    ) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Counts {
+        generations: usize,
+        steps: usize,
+        meals: usize,
+    }
+
+    struct Counter(Arc<Mutex<Counts>>);
+
+    impl SimulationObserver for Counter {
+        fn on_generation_start(&mut self, _generation: usize, _world: &World) {
+            self.0.lock().unwrap().generations += 1;
+        }
+
+        fn on_step(&mut self, _age: usize, _world: &World) {
+            self.0.lock().unwrap().steps += 1;
+        }
+
+        fn on_food_eaten(&mut self, _animal: &Animal, _food: &Food) {
+            self.0.lock().unwrap().meals += 1;
+        }
+    }
+
+    #[test]
+    fn every_observer_sees_every_step() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(&mut rng);
+        let first = Arc::new(Mutex::new(Counts::default()));
+        let second = Arc::new(Mutex::new(Counts::default()));
+        simulation.add_observer(Counter(first.clone()));
+        simulation.add_observer(Counter(second.clone()));
+
+        for _ in 0..300 {
+            simulation.step(&mut rng);
+        }
+
+        let eaten: usize = simulation.world().animals().iter().map(|animal| animal.satiation).sum();
+        for counts in [first, second] {
+            let counts = counts.lock().unwrap();
+            assert_eq!(counts.generations, 1);
+            assert_eq!(counts.steps, 300);
+            assert_eq!(counts.meals, eaten);
+        }
+    }
+}
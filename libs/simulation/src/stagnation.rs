@@ -0,0 +1,244 @@
+use crate::*;
+use ga::termination::{FitnessMetric, Stagnation};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StagnationResponse {
+    // Swap this fraction of the newborns for immigrants with fresh brains
+    RandomImmigrants { fraction: f32 },
+    // Multiply mutation chance and strength by `factor` for `generations`;
+    // only the genetic algorithm has a mutation to boost
+    MutationBoost { factor: f32, generations: usize },
+    // Keep the `elites` best brains from the hall of fame and start everyone
+    // else over with fresh brains
+    PartialRestart { elites: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StagnationEvent {
+    // The generation that just finished
+    pub generation: usize,
+    pub avg_satiation: f32,
+    pub responses: Vec<StagnationResponse>,
+}
+
+/// Reacts when average satiation hasn't improved by more than `tolerance`
+/// for `window` generations. Detection starts over after every reaction,
+/// giving the responses `window` generations to take effect.
+#[derive(Debug)]
+pub struct StagnationPolicy {
+    window: usize,
+    tolerance: f32,
+    responses: Vec<StagnationResponse>,
+    detector: Stagnation,
+    // Generations the current mutation boost has left
+    boost_left: usize,
+    events: Vec<StagnationEvent>,
+}
+
+impl StagnationPolicy {
+    pub fn new(window: usize, tolerance: f32) -> Self {
+        Self {
+            window,
+            tolerance,
+            responses: Vec::new(),
+            detector: Stagnation::new(window, tolerance, FitnessMetric::Mean),
+            boost_left: 0,
+            events: Vec::new(),
+        }
+    }
+
+    // Responses run in the order they were added
+    pub fn with_response(mut self, response: StagnationResponse) -> Self {
+        match response {
+            StagnationResponse::RandomImmigrants { fraction } => assert!((0.0..=1.0).contains(&fraction)),
+            StagnationResponse::MutationBoost { factor, .. } => assert!(factor > 0.0),
+            StagnationResponse::PartialRestart { .. } => {}
+        }
+        self.responses.push(response);
+        self
+    }
+
+    pub(crate) fn boosts_mutation(&self) -> bool {
+        self.responses
+            .iter()
+            .any(|response| matches!(response, StagnationResponse::MutationBoost { .. }))
+    }
+
+    // Every reaction so far, oldest first
+    pub fn events(&self) -> &[StagnationEvent] {
+        &self.events
+    }
+
+    fn check(&mut self, generation: usize, stats: &Statistics) -> Option<StagnationEvent> {
+        let progress = ga::termination::Progress {
+            generation,
+            best_fitness: stats.max as f32,
+            mean_fitness: stats.avg,
+            elapsed: Duration::ZERO,
+        };
+        self.detector.check(&progress)?;
+        self.detector = Stagnation::new(self.window, self.tolerance, FitnessMetric::Mean);
+
+        Some(StagnationEvent {
+            generation,
+            avg_satiation: stats.avg,
+            responses: self.responses.clone(),
+        })
+    }
+}
+
+impl Simulation {
+    // Called right after `evolve`, so responses act on the newborns. Fresh
+    // brains are built like the simulation's own, see `Simulation::configs`
+    pub(crate) fn respond_to_stagnation(&mut self, rng: &mut dyn RngCore, stats: &Statistics) {
+        let Some(policy) = &mut self.stagnation else {
+            return;
+        };
+
+        if policy.boost_left > 0 {
+            policy.boost_left -= 1;
+            if policy.boost_left == 0 {
+                self.ga.set_mutation_boost(1.0);
+            }
+        }
+
+        let Some(event) = policy.check(self.generation, stats) else {
+            return;
+        };
+
+        let configs = &self.configs;
+        let spawn = |rng: &mut dyn RngCore, index: usize| match configs.len() {
+            0 => Animal::random(rng),
+            len => Animal::from_config(rng, configs[index % len]),
+        };
+        let animals = &mut self.world.animals;
+        for &response in &event.responses {
+            match response {
+                StagnationResponse::RandomImmigrants { fraction } => {
+                    let count = (fraction * animals.len() as f32).round() as usize;
+                    let num_animals = animals.len();
+                    for (index, animal) in animals.iter_mut().enumerate().skip(num_animals - count) {
+                        *animal = spawn(rng, index);
+                    }
+                }
+                StagnationResponse::MutationBoost { factor, generations } => {
+                    self.ga.set_mutation_boost(factor);
+                    policy.boost_left = generations;
+                }
+                StagnationResponse::PartialRestart { elites } => {
+                    let elites: Vec<Chromosome> = self.hall_of_fame
+                        .iter()
+                        .take(elites)
+                        .map(|entry| entry.chromosome().clone())
+                        .collect();
+                    for (index, animal) in animals.iter_mut().enumerate() {
+                        *animal = match elites.get(index) {
+                            Some(elite) => Animal::from_chromosome(elite.clone(), configs, rng),
+                            None => spawn(rng, index),
+                        };
+                    }
+                }
+            }
+        }
+
+        if event.responses.iter().any(|response| !matches!(response, StagnationResponse::MutationBoost { .. })) {
+            self.ga.forget_lineage();
+        }
+
+        for observer in &mut self.observers {
+            observer.on_stagnation(&event);
+        }
+        policy.events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn flat() -> Statistics {
+        Statistics {
+            min: 0,
+            avg: 1.0,
+            max: 2,
+        }
+    }
+
+    #[test]
+    fn reacts_once_per_window() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let policy = StagnationPolicy::new(3, 0.1)
+            .with_response(StagnationResponse::RandomImmigrants { fraction: 0.25 })
+            .with_response(StagnationResponse::MutationBoost { factor: 10.0, generations: 2 });
+        let mut simulation = Simulation::random(&mut rng).with_stagnation_policy(policy);
+
+        let before: Vec<Chromosome> = simulation.world.animals.iter().map(Animal::as_chromosome).collect();
+        // The first generation sets the bar, the next three fail to beat it
+        for generation in 0..4 {
+            simulation.generation = generation;
+            simulation.respond_to_stagnation(&mut rng, &flat());
+        }
+
+        let policy = simulation.stagnation_policy().unwrap();
+        assert_eq!(policy.events().len(), 1);
+        assert_eq!(policy.events()[0].generation, 3);
+        assert_eq!(policy.boost_left, 2);
+
+        let replaced = simulation.world.animals
+            .iter()
+            .zip(&before)
+            .filter(|(animal, chromosome)| &animal.as_chromosome() != *chromosome)
+            .count();
+        assert_eq!(replaced, 10);
+    }
+
+    #[test]
+    fn immigrants_follow_the_config() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let config = Config::new(5, 2, 8, 0.25, 0.01, 0.3);
+        let policy = StagnationPolicy::new(1, 0.0).with_response(StagnationResponse::RandomImmigrants { fraction: 1.0 });
+        let mut simulation = Simulation::from_config(&mut rng, config).with_stagnation_policy(policy);
+        let expected = Animal::from_config(&mut rng, config).as_chromosome().len();
+        assert_ne!(Animal::random(&mut rng).as_chromosome().len(), expected);
+
+        simulation.respond_to_stagnation(&mut rng, &flat());
+        simulation.respond_to_stagnation(&mut rng, &flat());
+
+        assert_eq!(simulation.stagnation_policy().unwrap().events().len(), 1);
+        for animal in &simulation.world.animals {
+            assert_eq!(animal.as_chromosome().len(), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Mutation boosts only work with the genetic algorithm")]
+    fn mutation_boost_needs_the_genetic_algorithm() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let policy = StagnationPolicy::new(3, 0.1)
+            .with_response(StagnationResponse::MutationBoost { factor: 10.0, generations: 2 });
+        Simulation::random(&mut rng)
+            .with_stagnation_policy(policy)
+            .with_cma_es(&mut rng, 0.1);
+    }
+
+    #[test]
+    fn partial_restart_keeps_hall_of_fame() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let policy = StagnationPolicy::new(1, 0.0).with_response(StagnationResponse::PartialRestart { elites: 2 });
+        let mut simulation = Simulation::random(&mut rng).with_stagnation_policy(policy);
+
+        for (index, animal) in simulation.world.animals.iter_mut().enumerate() {
+            animal.satiation = index;
+        }
+        let champion = simulation.world.animals.last().unwrap().as_chromosome();
+        simulation.evolve(&mut rng);
+        simulation.respond_to_stagnation(&mut rng, &flat());
+        simulation.respond_to_stagnation(&mut rng, &flat());
+
+        assert_eq!(simulation.stagnation_policy().unwrap().events().len(), 1);
+        assert_eq!(simulation.world.animals[0].as_chromosome(), champion);
+    }
+}
@@ -8,35 +8,52 @@ pub struct World {
 
 impl World {
     pub fn random(rng: &mut dyn RngCore) -> Self {
-        let animals = (0..40)
+        let animals = (0..NUM_ANIMALS)
             .map(|_| Animal::random(rng))
             .collect();
 
-        let foods = (0..60)
+        let foods = (0..NUM_FOODS)
             .map(|_| Food::random(rng))
             .collect();
 
         Self{
-            animals: animals,
-            foods: foods,
+            animals,
+            foods,
         }
     }
 
     pub fn from_config(rng: &mut dyn RngCore, config: Config) -> Self {
-        let animals = (0..40)
+        let animals = (0..NUM_ANIMALS)
             .map(|_| Animal::from_config(rng, config))
             .collect();
-        let foods = (0..60)
+        let foods = (0..NUM_FOODS)
             .map(|_| Food::random(rng))
             .collect();
         
         Self{
-            animals: animals,
-            foods: foods,
+            animals,
+            foods,
         }
 
     }
 
+    // Animals are split evenly between `configs`, e.g. to evolve different
+    // brain topologies side by side
+    pub fn from_configs(rng: &mut dyn RngCore, configs: &[Config]) -> Self {
+        assert!(!configs.is_empty());
+        let animals = (0..NUM_ANIMALS)
+            .map(|index| Animal::from_config(rng, configs[index % configs.len()]))
+            .collect();
+        let foods = (0..NUM_FOODS)
+            .map(|_| Food::random(rng))
+            .collect();
+
+        Self{
+            animals,
+            foods,
+        }
+    }
+
     pub fn animals(&self) -> &[Animal] {
         &self.animals
     }